@@ -0,0 +1,171 @@
+//! `axum` extractor for IDs in request paths.
+//!
+//! [`ShortId`] implements `FromRequestParts`, so a handler can take an ID from a route
+//! with a single path parameter directly, instead of extracting a `Path<String>` and
+//! converting it:
+//!
+//! ```
+//! use axum::{routing::get, Router};
+//! use short_id::ShortId;
+//!
+//! async fn get_user(id: ShortId) -> String {
+//!     format!("user {}", id)
+//! }
+//!
+//! let app: Router = Router::new().route("/users/{id}", get(get_user));
+//! ```
+//!
+//! A parameter that is not a valid ID is rejected with [`IdRejection`], which responds with
+//! `400 Bad Request` and a plain-text body naming the problem, e.g.
+//! `invalid path parameter: invalid short ID encoding`.
+
+use ::axum::{
+    extract::{rejection::PathRejection, FromRequestParts, Path},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::{DecodeError, ShortId};
+
+impl<S: Send + Sync> FromRequestParts<S> for ShortId {
+    type Rejection = IdRejection;
+
+    /// Extracts the ID from the route's only path parameter, validating its encoding.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(id) = Path::<String>::from_request_parts(parts, state).await?;
+        Ok(ShortId::parse(&id)?)
+    }
+}
+
+/// Rejection returned when a [`ShortId`] cannot be extracted from a request.
+///
+/// **This type requires the `axum` feature.**
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IdRejection {
+    /// The path parameter is not a valid ID encoding.
+    ///
+    /// Responds with `400 Bad Request` and the body `invalid path parameter: <error>`.
+    InvalidId(DecodeError),
+    /// The path parameter could not be read, e.g. because the route has more than one.
+    ///
+    /// Responds the same way as `axum`'s own `Path` extractor.
+    Path(PathRejection),
+}
+
+impl From<DecodeError> for IdRejection {
+    fn from(error: DecodeError) -> Self {
+        IdRejection::InvalidId(error)
+    }
+}
+
+impl From<PathRejection> for IdRejection {
+    fn from(rejection: PathRejection) -> Self {
+        IdRejection::Path(rejection)
+    }
+}
+
+impl IdRejection {
+    /// Returns the status code of the response.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            IdRejection::InvalidId(_) => StatusCode::BAD_REQUEST,
+            IdRejection::Path(rejection) => rejection.status(),
+        }
+    }
+
+    /// Returns the body of the response.
+    pub fn body_text(&self) -> String {
+        match self {
+            IdRejection::InvalidId(error) => format!("invalid path parameter: {}", error),
+            IdRejection::Path(rejection) => rejection.body_text(),
+        }
+    }
+}
+
+impl IntoResponse for IdRejection {
+    fn into_response(self) -> Response {
+        match self {
+            IdRejection::InvalidId(_) => (self.status(), self.body_text()).into_response(),
+            IdRejection::Path(rejection) => rejection.into_response(),
+        }
+    }
+}
+
+impl core::fmt::Display for IdRejection {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.body_text())
+    }
+}
+
+impl std::error::Error for IdRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IdRejection::InvalidId(error) => Some(error),
+            IdRejection::Path(rejection) => Some(rejection),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::axum::{
+        body::{to_bytes, Body},
+        http::Request,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/users/{id}",
+                get(|id: ShortId| async move { id.to_string() }),
+            )
+            .route(
+                "/users/{id}/posts/{post}",
+                get(|id: ShortId| async move { id.to_string() }),
+            )
+    }
+
+    async fn request(uri: &str) -> (StatusCode, String) {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_extracts_valid_id() {
+        let id = ShortId::random();
+        let (status, body) = request(&format!("/users/{}", id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_id() {
+        let (status, body) = request("/users/not%20valid!").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, "invalid path parameter: invalid short ID encoding");
+
+        // A length no byte sequence encodes to
+        let (status, body) = request("/users/A").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            format!("invalid path parameter: {}", DecodeError::InvalidEncoding)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_path_errors_use_axum_response() {
+        let (status, body) = request(&format!("/users/{}/posts/1", ShortId::random())).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(body.contains("Wrong number of path arguments"), "{}", body);
+    }
+}
@@ -0,0 +1,466 @@
+//! Bech32m encoding of IDs (BIP-350).
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::{generate_random_id, ShortId};
+
+/// The Bech32 data alphabet, indexed by 5-bit value.
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Bech32m checksum constant.
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+/// Generator coefficients of the BCH code.
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+/// Separator between the human-readable part and the data.
+const SEPARATOR: char = '1';
+
+/// Number of checksum characters.
+const CHECKSUM_LEN: usize = 6;
+
+/// Maximum length of a Bech32m string.
+const MAX_LEN: usize = 90;
+
+/// Generates a random ID in Bech32m format with the human-readable prefix `hrp`.
+///
+/// The ID carries the same 10 random bytes as [`short_id()`](crate::short_id), encoded
+/// as `<hrp>1<data><checksum>`: 16 data characters from the Bech32 alphabet
+/// (`qpzry9x8gf2tvdw0s3jn54khce6mua7l`, lowercase, without the look-alikes `1`, `b`, `i`
+/// and `o`), followed by a 6-character BCH checksum that detects any 4 mistyped
+/// characters. Bech32m strings are double-click selectable and survive case changes.
+///
+/// # Panics
+///
+/// Panics if `hrp` is not a valid human-readable part: 1 to 83 ASCII characters in the
+/// range `!` to `~`, not mixing upper and lower case.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{short_id_bech32, ShortId};
+///
+/// let id = short_id_bech32("user");
+/// assert!(id.starts_with("user1"));
+/// assert_eq!(id.len(), 4 + 1 + 16 + 6);
+///
+/// let parsed = ShortId::from_bech32(&id, "user").unwrap();
+/// assert_eq!(parsed.to_bytes().unwrap().len(), 10);
+/// ```
+pub fn short_id_bech32(hrp: &str) -> String {
+    ShortId(generate_random_id(10))
+        .to_bech32(hrp)
+        .expect("invalid human-readable part")
+}
+
+/// Generates a time-ordered ID in Bech32m format with the human-readable prefix `hrp`.
+///
+/// The ID carries the same bytes as [`short_id_ordered()`](crate::short_id_ordered).
+/// These IDs do **not** sort by creation time as strings; parse them with
+/// [`ShortId::from_bech32()`] to sort by their bytes or decode the timestamp.
+///
+/// **This function requires the `std` feature** (enabled by default).
+///
+/// # Panics
+///
+/// Panics if `hrp` is not a valid human-readable part (see [`short_id_bech32()`]).
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{short_id_ordered_bech32, OrderedLayout, ShortId};
+///
+/// let id = short_id_ordered_bech32("evt");
+/// let plain = ShortId::from_bech32(&id, "evt").unwrap();
+/// assert!(OrderedLayout::DEFAULT.timestamp(plain.as_str()).is_ok());
+/// ```
+#[cfg(feature = "std")]
+pub fn short_id_ordered_bech32(hrp: &str) -> String {
+    ShortId(crate::short_id_ordered())
+        .to_bech32(hrp)
+        .expect("invalid human-readable part")
+}
+
+impl ShortId {
+    /// Encodes the ID's bytes in Bech32m format with the human-readable prefix `hrp`.
+    ///
+    /// The result is lowercase, even if `hrp` is uppercase.
+    ///
+    /// # Errors
+    ///
+    /// - [`Bech32Error::Decode`] if the ID is not a valid encoding
+    /// - [`Bech32Error::InvalidHrp`] if `hrp` is empty, longer than 83 characters, mixes
+    ///   case or contains characters outside `!` to `~`
+    /// - [`Bech32Error::TooLong`] if the result would exceed 90 characters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::ShortId;
+    ///
+    /// let id = ShortId::from_bytes(&[0, 1, 2, 3, 4]);
+    /// assert_eq!(id.to_bech32("id").unwrap(), "id1qqqsyqcygzmvm3");
+    /// ```
+    pub fn to_bech32(&self, hrp: &str) -> Result<String, Bech32Error> {
+        check_hrp(hrp)?;
+        let data = to_base32(&self.to_bytes()?);
+        let len = hrp.len() + 1 + data.len() + CHECKSUM_LEN;
+        if len > MAX_LEN {
+            return Err(Bech32Error::TooLong(len));
+        }
+
+        let hrp = hrp.to_ascii_lowercase();
+        let checksum = checksum(&hrp, &data);
+        let mut encoded = String::with_capacity(len);
+        encoded.push_str(&hrp);
+        encoded.push(SEPARATOR);
+        for value in data.iter().chain(&checksum) {
+            encoded.push(CHARSET[*value as usize] as char);
+        }
+        Ok(encoded)
+    }
+
+    /// Parses a Bech32m string with the human-readable prefix `hrp` back into an ID.
+    ///
+    /// The prefix is compared case-insensitively, and all-uppercase strings (e.g. for QR
+    /// codes) are accepted.
+    ///
+    /// # Errors
+    ///
+    /// - [`Bech32Error::HrpMismatch`] if the string has a different prefix
+    /// - [`Bech32Error::InvalidChecksum`] if the string was mistyped
+    /// - other [`Bech32Error`] variants if the string is not Bech32m at all
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{Bech32Error, ShortId};
+    ///
+    /// let id = ShortId::random();
+    /// let encoded = id.to_bech32("user").unwrap();
+    /// assert_eq!(ShortId::from_bech32(&encoded, "user"), Ok(id));
+    ///
+    /// // Mistyped strings fail the checksum
+    /// let typo = encoded.replacen("user1", "user1q", 1);
+    /// assert!(ShortId::from_bech32(&typo, "user").is_err());
+    ///
+    /// // Prefixes are checked
+    /// assert_eq!(ShortId::from_bech32(&encoded, "org"), Err(Bech32Error::HrpMismatch));
+    /// ```
+    pub fn from_bech32(encoded: &str, hrp: &str) -> Result<Self, Bech32Error> {
+        let (actual_hrp, data) = decode_raw(encoded)?;
+        if !actual_hrp.eq_ignore_ascii_case(hrp) {
+            return Err(Bech32Error::HrpMismatch);
+        }
+        Ok(ShortId::from_bytes(&from_base32(&data)?))
+    }
+}
+
+/// Error returned when a Bech32m string cannot be encoded or parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Bech32Error {
+    /// The ID to encode is not a valid encoding.
+    Decode(crate::DecodeError),
+    /// The human-readable part is empty, too long, mixes case, or contains characters
+    /// outside `!` to `~`.
+    InvalidHrp,
+    /// The string has no `1` separating the human-readable part from the data.
+    MissingSeparator,
+    /// The data part contains a character outside the Bech32 alphabet.
+    InvalidCharacter(char),
+    /// The string mixes upper and lower case.
+    MixedCase,
+    /// The string (or the string an encoding would produce) exceeds 90 characters.
+    TooLong(usize),
+    /// The checksum does not match: the string was mistyped or is not Bech32m.
+    InvalidChecksum,
+    /// The data does not convert back to whole bytes.
+    InvalidPadding,
+    /// The human-readable part is not the expected one.
+    HrpMismatch,
+}
+
+impl From<crate::DecodeError> for Bech32Error {
+    fn from(error: crate::DecodeError) -> Self {
+        Bech32Error::Decode(error)
+    }
+}
+
+impl core::fmt::Display for Bech32Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Bech32Error::Decode(error) => write!(f, "{}", error),
+            Bech32Error::InvalidHrp => write!(f, "invalid Bech32 human-readable part"),
+            Bech32Error::MissingSeparator => write!(f, "missing Bech32 separator"),
+            Bech32Error::InvalidCharacter(c) => write!(f, "invalid Bech32 character {:?}", c),
+            Bech32Error::MixedCase => write!(f, "Bech32 string mixes upper and lower case"),
+            Bech32Error::TooLong(len) => {
+                write!(f, "Bech32 string too long ({} characters)", len)
+            }
+            Bech32Error::InvalidChecksum => write!(f, "invalid Bech32m checksum"),
+            Bech32Error::InvalidPadding => write!(f, "invalid Bech32 padding"),
+            Bech32Error::HrpMismatch => write!(f, "unexpected Bech32 human-readable part"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Bech32Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Bech32Error::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Checks that `hrp` is a valid human-readable part.
+fn check_hrp(hrp: &str) -> Result<(), Bech32Error> {
+    let valid_chars = hrp.bytes().all(|byte| (33..=126).contains(&byte));
+    let mixed_case = hrp.bytes().any(|byte| byte.is_ascii_lowercase())
+        && hrp.bytes().any(|byte| byte.is_ascii_uppercase());
+    if hrp.is_empty() || hrp.len() > 83 || !valid_chars || mixed_case {
+        return Err(Bech32Error::InvalidHrp);
+    }
+    Ok(())
+}
+
+/// Splits a Bech32m string into its lowercase human-readable part and 5-bit data values,
+/// verifying the checksum.
+fn decode_raw(encoded: &str) -> Result<(String, Vec<u8>), Bech32Error> {
+    if encoded.len() > MAX_LEN {
+        return Err(Bech32Error::TooLong(encoded.len()));
+    }
+    if encoded.bytes().any(|byte| byte.is_ascii_lowercase())
+        && encoded.bytes().any(|byte| byte.is_ascii_uppercase())
+    {
+        return Err(Bech32Error::MixedCase);
+    }
+    let encoded = encoded.to_ascii_lowercase();
+
+    let separator = encoded
+        .rfind(SEPARATOR)
+        .ok_or(Bech32Error::MissingSeparator)?;
+    let (hrp, data) = (&encoded[..separator], &encoded[separator + 1..]);
+    check_hrp(hrp)?;
+    if data.len() < CHECKSUM_LEN {
+        return Err(Bech32Error::InvalidChecksum);
+    }
+
+    let values = data
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|&byte| byte as char == c)
+                .map(|value| value as u8)
+                .ok_or(Bech32Error::InvalidCharacter(c))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    let mut check = hrp_expand(hrp);
+    check.extend_from_slice(&values);
+    if polymod(&check) != BECH32M_CONST {
+        return Err(Bech32Error::InvalidChecksum);
+    }
+
+    let data_len = values.len() - CHECKSUM_LEN;
+    Ok((String::from(hrp), values[..data_len].to_vec()))
+}
+
+/// Computes the BCH checksum polynomial over 5-bit values.
+fn polymod(values: &[u8]) -> u32 {
+    values.iter().fold(1u32, |chk, &value| {
+        let top = chk >> 25;
+        let mut chk = ((chk & 0x01ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+        chk
+    })
+}
+
+/// Expands the human-readable part into the values it contributes to the checksum.
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|byte| byte >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|byte| byte & 31));
+    expanded
+}
+
+/// Computes the 6 checksum values for `data` under the lowercase `hrp`.
+fn checksum(hrp: &str, data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; CHECKSUM_LEN]);
+    let polymod = polymod(&values) ^ BECH32M_CONST;
+
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    for (i, value) in checksum.iter_mut().enumerate() {
+        *value = ((polymod >> (5 * (5 - i))) & 31) as u8;
+    }
+    checksum
+}
+
+/// Regroups bytes into 5-bit values, padding the last one with zero bits.
+fn to_base32(bytes: &[u8]) -> Vec<u8> {
+    let mut values = Vec::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &byte in bytes {
+        acc = (acc << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            values.push(((acc >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        values.push(((acc << (5 - bits)) & 31) as u8);
+    }
+    values
+}
+
+/// Regroups 5-bit values into bytes, rejecting non-zero or overlong padding.
+fn from_base32(values: &[u8]) -> Result<Vec<u8>, Bech32Error> {
+    let mut bytes = Vec::with_capacity(values.len() * 5 / 8);
+    let (mut acc, mut bits) = (0u32, 0u32);
+    for &value in values {
+        acc = (acc << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    if bits >= 5 || acc & ((1 << bits) - 1) != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_bip350_valid_vectors() {
+        for valid in [
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11sg7hg6",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "11llllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllludsr8",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ] {
+            assert!(decode_raw(valid).is_ok(), "{} should be valid", valid);
+        }
+    }
+
+    #[test]
+    fn test_bip350_invalid_vectors() {
+        for (invalid, error) in [
+            ("a1lqfn3a ", Bech32Error::InvalidCharacter(' ')),
+            ("qyrz8wqd2c9m", Bech32Error::MissingSeparator),
+            ("1qyrz8wqd2c9m", Bech32Error::InvalidHrp),
+            ("y1b0jsk6g", Bech32Error::InvalidCharacter('b')),
+            ("lt1igcx5c0", Bech32Error::InvalidCharacter('i')),
+            ("in1muywd", Bech32Error::InvalidChecksum),
+            ("mm1crxm3i", Bech32Error::InvalidCharacter('i')),
+            ("au1s5cgom", Bech32Error::InvalidCharacter('o')),
+            ("M1VUXWEZ", Bech32Error::InvalidChecksum),
+            ("16plkw9", Bech32Error::InvalidHrp),
+            ("1p2gdwpf", Bech32Error::InvalidHrp),
+            ("A1lqfn3a", Bech32Error::MixedCase),
+        ] {
+            assert_eq!(decode_raw(invalid), Err(error), "{}", invalid);
+        }
+        assert!(matches!(
+            decode_raw(&"a".repeat(91)),
+            Err(Bech32Error::TooLong(91))
+        ));
+    }
+
+    #[test]
+    fn test_roundtrip_all_lengths() {
+        for len in 1..=32 {
+            let id = ShortId(crate::short_id_with_bytes(len));
+            let encoded = id.to_bech32("id").unwrap();
+            assert_eq!(ShortId::from_bech32(&encoded, "id"), Ok(id));
+        }
+    }
+
+    #[test]
+    fn test_detects_single_character_errors() {
+        let encoded = short_id_bech32("user");
+        for position in 5..encoded.len() {
+            for &replacement in CHARSET {
+                let mut typo = encoded.clone().into_bytes();
+                if typo[position] == replacement {
+                    continue;
+                }
+                typo[position] = replacement;
+                let typo = String::from_utf8(typo).unwrap();
+                assert_eq!(
+                    ShortId::from_bech32(&typo, "user"),
+                    Err(Bech32Error::InvalidChecksum),
+                    "{}",
+                    typo
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_uppercase_strings_are_accepted() {
+        let id = ShortId::random();
+        let encoded = id.to_bech32("user").unwrap().to_uppercase();
+        assert_eq!(ShortId::from_bech32(&encoded, "user"), Ok(id.clone()));
+        assert_eq!(id.to_bech32("USER").unwrap(), id.to_bech32("user").unwrap());
+    }
+
+    #[test]
+    fn test_rejects_invalid_hrp() {
+        let id = ShortId::random();
+        assert_eq!(id.to_bech32(""), Err(Bech32Error::InvalidHrp));
+        assert_eq!(id.to_bech32("Mixed"), Err(Bech32Error::InvalidHrp));
+        assert_eq!(id.to_bech32("with space"), Err(Bech32Error::InvalidHrp));
+        assert_eq!(
+            ShortId(crate::short_id_with_bytes(32)).to_bech32(&"x".repeat(40)),
+            Err(Bech32Error::TooLong(99))
+        );
+    }
+
+    #[test]
+    fn test_rejects_nonzero_padding() {
+        // One 5-bit value cannot hold a whole byte
+        let checksum = checksum("id", &[0]);
+        let mut encoded = "id1q".to_string();
+        for value in checksum {
+            encoded.push(CHARSET[value as usize] as char);
+        }
+        assert_eq!(
+            ShortId::from_bech32(&encoded, "id"),
+            Err(Bech32Error::InvalidPadding)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid human-readable part")]
+    fn test_generate_with_invalid_hrp_panics() {
+        short_id_bech32("");
+    }
+}
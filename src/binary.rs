@@ -0,0 +1,144 @@
+//! Storing IDs as their raw bytes.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{DecodeError, ShortId};
+
+/// A [`ShortId`] stored in its compact binary form.
+///
+/// The database integrations store a `ShortId` as text by default, which keeps columns
+/// readable in a SQL shell. Wrapping it in `BinaryId` stores the bytes the ID decodes to
+/// instead (`BLOB` in SQLite, `BYTEA` in Postgres): a default 14-character ID takes 10
+/// bytes.
+///
+/// Base64url characters are not in ASCII order, so comparing two IDs as strings does not
+/// always compare their bytes. `BinaryId` orders IDs by the bytes they decode to, the way
+/// an index over a binary column does, which keeps time-ordered IDs in creation order.
+///
+/// Every byte sequence is a valid ID, so reading a binary column cannot fail on the
+/// contents. Writing one fails if the `ShortId` was built from a string that is not a
+/// valid encoding (see [`From<String>`](ShortId#impl-From<String>-for-ShortId)).
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{BinaryId, ShortId};
+///
+/// let id = ShortId::random();
+/// let binary = BinaryId::from(id.clone());
+/// assert_eq!(binary.to_bytes().unwrap().len(), 10);
+/// assert_eq!(BinaryId::from_bytes(&binary.to_bytes().unwrap()).into_inner(), id);
+/// ```
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BinaryId(pub ShortId);
+
+/// Returns the position of an ID character in the byte order of the decoded ID.
+///
+/// Valid characters map to the 6-bit values they encode; anything else sorts after them.
+fn sort_key(c: u8) -> u16 {
+    u16::from(match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'-' => 62,
+        b'_' => 63,
+        _ => return 64 + u16::from(c),
+    })
+}
+
+impl Ord for BinaryId {
+    /// Compares the bytes the IDs decode to.
+    ///
+    /// Comparing the 6-bit values of the characters gives the same result as decoding both
+    /// IDs, without allocating. IDs that are not valid encodings still get a consistent
+    /// total order.
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        let a = self.0.as_str().bytes().map(sort_key);
+        let b = other.0.as_str().bytes().map(sort_key);
+        a.cmp(b)
+    }
+}
+
+impl PartialOrd for BinaryId {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl BinaryId {
+    /// Creates a binary ID from the raw bytes read from storage.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        BinaryId(ShortId::from_bytes(bytes))
+    }
+
+    /// Returns the raw bytes to write to storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if the wrapped ID is not a valid encoding.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DecodeError> {
+        self.0.to_bytes()
+    }
+
+    /// Returns the wrapped ID.
+    pub fn into_inner(self) -> ShortId {
+        self.0
+    }
+}
+
+impl From<ShortId> for BinaryId {
+    fn from(id: ShortId) -> Self {
+        BinaryId(id)
+    }
+}
+
+impl From<BinaryId> for ShortId {
+    fn from(id: BinaryId) -> Self {
+        id.0
+    }
+}
+
+impl core::fmt::Display for BinaryId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::String;
+
+    #[test]
+    fn test_roundtrip() {
+        let id = ShortId::random();
+        let bytes = BinaryId::from(id.clone()).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 10);
+        assert_eq!(ShortId::from(BinaryId::from_bytes(&bytes)), id);
+    }
+
+    #[test]
+    fn test_sorts_by_bytes() {
+        let mut ids: Vec<BinaryId> = (0..100)
+            .map(|i| BinaryId::from(ShortId::from(crate::short_id_with_bytes(1 + i % 12))))
+            .collect();
+        ids.push(BinaryId::from_bytes(&[0x00]));
+        ids.push(BinaryId::from_bytes(&[0x00, 0xff]));
+        ids.push(BinaryId::from_bytes(&[0xff]));
+        for a in &ids {
+            for b in &ids {
+                let (x, y) = (a.to_bytes().unwrap(), b.to_bytes().unwrap());
+                assert_eq!(a.cmp(b), x.cmp(&y), "{} {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_id_fails_to_encode() {
+        let binary = BinaryId(ShortId::from(String::from("not valid!")));
+        assert_eq!(binary.to_bytes(), Err(DecodeError::InvalidEncoding));
+    }
+}
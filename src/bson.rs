@@ -0,0 +1,221 @@
+//! BSON conversions for [`ShortId`] and [`BinaryId`].
+
+use ::bson::{
+    spec::{BinarySubtype, ElementType},
+    Binary, Bson,
+};
+
+use crate::{BinaryId, DecodeError, ShortId};
+
+impl BinaryId {
+    /// The user-defined BSON binary subtype IDs are stored with.
+    ///
+    /// Subtypes `0x80` to `0xff` are reserved for applications, so IDs can be told apart
+    /// from other binary data in the same collection.
+    ///
+    /// **This constant requires the `bson` feature.**
+    pub const BSON_SUBTYPE: u8 = 0x80;
+}
+
+impl From<ShortId> for Bson {
+    /// Converts the ID to a BSON string.
+    fn from(id: ShortId) -> Self {
+        Bson::String(id.0)
+    }
+}
+
+impl TryFrom<BinaryId> for Bson {
+    type Error = DecodeError;
+
+    /// Converts the ID to BSON binary data with subtype [`BinaryId::BSON_SUBTYPE`].
+    ///
+    /// MongoDB compares binary values by length, then subtype, then bytes, so IDs of the
+    /// same length sort by their bytes, and ordered IDs in creation order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if the wrapped ID is not a valid encoding.
+    fn try_from(id: BinaryId) -> Result<Self, Self::Error> {
+        Ok(Bson::Binary(Binary {
+            subtype: BinarySubtype::UserDefined(BinaryId::BSON_SUBTYPE),
+            bytes: id.to_bytes()?,
+        }))
+    }
+}
+
+impl TryFrom<&Bson> for ShortId {
+    type Error = BsonError;
+
+    /// Reads an ID stored as a BSON string or as binary data with subtype
+    /// [`BinaryId::BSON_SUBTYPE`].
+    ///
+    /// # Errors
+    ///
+    /// - [`BsonError::Decode`] if a string is not a valid ID encoding
+    /// - [`BsonError::UnexpectedSubtype`] for binary data with another subtype
+    /// - [`BsonError::UnexpectedType`] for any other BSON type
+    fn try_from(value: &Bson) -> Result<Self, Self::Error> {
+        match value {
+            Bson::String(id) => Ok(ShortId::parse(id)?),
+            Bson::Binary(binary) => {
+                if binary.subtype != BinarySubtype::UserDefined(BinaryId::BSON_SUBTYPE) {
+                    return Err(BsonError::UnexpectedSubtype(binary.subtype));
+                }
+                Ok(ShortId::from_bytes(&binary.bytes))
+            }
+            other => Err(BsonError::UnexpectedType(other.element_type())),
+        }
+    }
+}
+
+impl TryFrom<Bson> for ShortId {
+    type Error = BsonError;
+
+    fn try_from(value: Bson) -> Result<Self, Self::Error> {
+        ShortId::try_from(&value)
+    }
+}
+
+impl TryFrom<&Bson> for BinaryId {
+    type Error = BsonError;
+
+    /// Reads an ID stored in either form; see [`TryFrom<&Bson> for ShortId`](ShortId).
+    fn try_from(value: &Bson) -> Result<Self, Self::Error> {
+        ShortId::try_from(value).map(BinaryId)
+    }
+}
+
+impl TryFrom<Bson> for BinaryId {
+    type Error = BsonError;
+
+    fn try_from(value: Bson) -> Result<Self, Self::Error> {
+        BinaryId::try_from(&value)
+    }
+}
+
+/// Error returned when a BSON value cannot be converted to an ID.
+///
+/// **This type requires the `bson` feature.**
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BsonError {
+    /// The value is a string that is not a valid ID encoding.
+    Decode(DecodeError),
+    /// The value is neither a string nor binary data.
+    UnexpectedType(ElementType),
+    /// The value is binary data with a subtype other than [`BinaryId::BSON_SUBTYPE`].
+    UnexpectedSubtype(BinarySubtype),
+}
+
+impl From<DecodeError> for BsonError {
+    fn from(error: DecodeError) -> Self {
+        BsonError::Decode(error)
+    }
+}
+
+impl core::fmt::Display for BsonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BsonError::Decode(error) => write!(f, "{}", error),
+            BsonError::UnexpectedType(ty) => write!(f, "unexpected BSON type for an ID: {:?}", ty),
+            BsonError::UnexpectedSubtype(subtype) => {
+                write!(f, "unexpected BSON binary subtype for an ID: {:?}", subtype)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BsonError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::bson::doc;
+
+    #[test]
+    fn test_string_roundtrip() {
+        let id = ShortId::random();
+        let doc = doc! { "_id": id.clone() };
+        assert_eq!(doc.get("_id"), Some(&Bson::String(id.to_string())));
+        assert_eq!(ShortId::try_from(doc.get("_id").unwrap()), Ok(id));
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let id = ShortId::random();
+        let value = Bson::try_from(BinaryId(id.clone())).unwrap();
+        match &value {
+            Bson::Binary(binary) => {
+                assert_eq!(binary.subtype, BinarySubtype::UserDefined(0x80));
+                assert_eq!(binary.bytes, id.to_bytes().unwrap());
+            }
+            other => panic!("expected binary, got {:?}", other),
+        }
+        assert_eq!(ShortId::try_from(&value), Ok(id.clone()));
+        assert_eq!(BinaryId::try_from(value), Ok(BinaryId(id)));
+    }
+
+    #[test]
+    fn test_rejects_other_values() {
+        assert_eq!(
+            ShortId::try_from(Bson::String(String::from("not valid!"))),
+            Err(BsonError::Decode(DecodeError::InvalidEncoding))
+        );
+        assert_eq!(
+            ShortId::try_from(Bson::Int64(42)),
+            Err(BsonError::UnexpectedType(ElementType::Int64))
+        );
+        let uuid = Bson::Binary(Binary {
+            subtype: BinarySubtype::Uuid,
+            bytes: vec![0; 16],
+        });
+        assert_eq!(
+            ShortId::try_from(uuid),
+            Err(BsonError::UnexpectedSubtype(BinarySubtype::Uuid))
+        );
+        assert_eq!(
+            Bson::try_from(BinaryId(ShortId::from(String::from("not valid!")))),
+            Err(DecodeError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_ordered_ids_sort_as_binary() {
+        let mut ids = Vec::new();
+        for _ in 0..20 {
+            ids.push(ShortId::ordered());
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        let mut binaries: Vec<Binary> = ids
+            .iter()
+            .rev()
+            .map(|id| match Bson::try_from(BinaryId(id.clone())).unwrap() {
+                Bson::Binary(binary) => binary,
+                _ => unreachable!(),
+            })
+            .collect();
+        // MongoDB's order for binary data: length, then subtype, then bytes
+        binaries.sort_by(|a, b| {
+            (a.bytes.len(), u8::from(a.subtype), &a.bytes).cmp(&(
+                b.bytes.len(),
+                u8::from(b.subtype),
+                &b.bytes,
+            ))
+        });
+
+        let sorted: Vec<ShortId> = binaries
+            .iter()
+            .map(|binary| ShortId::from_bytes(&binary.bytes))
+            .collect();
+        assert_eq!(sorted, ids);
+    }
+}
@@ -0,0 +1,231 @@
+//! Check characters for detecting typos in IDs.
+//!
+//! The check character is computed with the Damm algorithm over the 64 characters of the
+//! ID alphabet. Characters are mapped to their index in the alphabet and treated as
+//! elements of GF(64) (modulo x⁶ + x + 1). The quasigroup operation `x ∘ y = 2·x ⊕ y` is
+//! totally anti-symmetric, so the check detects every single-character substitution and
+//! every transposition of adjacent characters, including the check character itself.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use crate::{short_id, DecodeError, ShortId, ALPHABET};
+
+/// Multiplies a GF(64) element by 2 (the generator `x`), reducing by x⁶ + x + 1.
+const fn double(value: u8) -> u8 {
+    let shifted = value << 1;
+    if shifted & 0x40 != 0 {
+        shifted ^ 0x43
+    } else {
+        shifted
+    }
+}
+
+/// Returns the index of `byte` in the ID alphabet.
+fn value_of(byte: u8) -> Option<u8> {
+    ALPHABET
+        .bytes()
+        .position(|c| c == byte)
+        .map(|index| index as u8)
+}
+
+/// Runs the Damm algorithm over `id`, returning `None` for characters outside the
+/// alphabet.
+///
+/// Appending the character at index `2·interim` to a string drives the interim back to
+/// zero, so a string ending in its check character always folds to zero.
+fn interim(id: &str) -> Option<u8> {
+    id.bytes()
+        .try_fold(0u8, |interim, byte| Some(double(interim) ^ value_of(byte)?))
+}
+
+/// Computes the check character of `id`.
+fn check_char(id: &str) -> Result<char, DecodeError> {
+    let interim = interim(id).ok_or(DecodeError::InvalidEncoding)?;
+    Ok(ALPHABET.as_bytes()[double(interim) as usize] as char)
+}
+
+/// Generates a random short ID followed by a check character.
+///
+/// The result is a 15-character string: the 14 characters of a [`short_id()`] plus one
+/// check character, which lets [`ShortId::parse_checked()`] reject IDs mistyped by
+/// people, e.g. when read out on a support call. Every single-character error and every
+/// swap of two adjacent characters is detected.
+///
+/// Checked strings are not plain IDs: strip the check character with
+/// [`ShortId::parse_checked()`] before decoding or storing them.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{short_id_checked, ShortId};
+///
+/// let checked = short_id_checked();
+/// assert_eq!(checked.len(), 15);
+///
+/// let id = ShortId::parse_checked(&checked).unwrap();
+/// assert_eq!(id.as_str(), &checked[..14]);
+/// ```
+pub fn short_id_checked() -> String {
+    ShortId(short_id()).to_checked_string()
+}
+
+impl ShortId {
+    /// Returns the ID followed by its check character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::ShortId;
+    ///
+    /// let id = ShortId::random();
+    /// let checked = id.to_checked_string();
+    /// assert_eq!(checked.len(), 15);
+    /// assert_eq!(ShortId::parse_checked(&checked), Ok(id));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ID was built from a string containing characters outside the ID
+    /// alphabet (see [`From<String>`](#impl-From<String>-for-ShortId)).
+    pub fn to_checked_string(&self) -> String {
+        let check = check_char(&self.0).expect("ID contains characters outside the alphabet");
+        let mut checked = String::with_capacity(self.0.len() + 1);
+        checked.push_str(&self.0);
+        checked.push(check);
+        checked
+    }
+
+    /// Parses an ID followed by its check character, as produced by
+    /// [`to_checked_string()`](Self::to_checked_string) or [`short_id_checked()`].
+    ///
+    /// # Errors
+    ///
+    /// - [`DecodeError::InvalidCheckCharacter`] if the check character doesn't match,
+    ///   which means the string was mistyped
+    /// - [`DecodeError::InvalidEncoding`] if the string contains characters outside the
+    ///   alphabet, or the part before the check character is not a valid ID
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{DecodeError, ShortId};
+    ///
+    /// let checked = ShortId::from_bytes(&[1, 2, 3, 4, 5, 6]).to_checked_string();
+    /// assert!(ShortId::parse_checked(&checked).is_ok());
+    ///
+    /// // Swap two adjacent characters
+    /// let mut typo: Vec<char> = checked.chars().collect();
+    /// typo.swap(3, 4);
+    /// let typo: String = typo.into_iter().collect();
+    /// assert_eq!(ShortId::parse_checked(&typo), Err(DecodeError::InvalidCheckCharacter));
+    /// ```
+    pub fn parse_checked(checked: &str) -> Result<Self, DecodeError> {
+        match interim(checked) {
+            None => Err(DecodeError::InvalidEncoding),
+            Some(0) if !checked.is_empty() => ShortId::parse(&checked[..checked.len() - 1]),
+            Some(_) => Err(DecodeError::InvalidCheckCharacter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    const CHECKED_LEN: usize = 15;
+
+    #[test]
+    fn test_doubling_is_a_permutation() {
+        let mut seen = [false; 64];
+        for value in 0..64 {
+            let doubled = double(value);
+            assert!(doubled < 64);
+            assert!(!seen[doubled as usize]);
+            seen[doubled as usize] = true;
+        }
+    }
+
+    #[test]
+    fn test_checked_ids_roundtrip() {
+        for _ in 0..100 {
+            let checked = short_id_checked();
+            assert_eq!(checked.len(), CHECKED_LEN);
+            let id = ShortId::parse_checked(&checked).unwrap();
+            assert_eq!(id.to_checked_string(), checked);
+        }
+    }
+
+    #[test]
+    fn test_detects_every_single_character_error() {
+        let checked = short_id_checked();
+        for position in 0..CHECKED_LEN {
+            for replacement in ALPHABET.bytes() {
+                let mut typo = checked.clone().into_bytes();
+                if typo[position] == replacement {
+                    continue;
+                }
+                typo[position] = replacement;
+                let typo = String::from_utf8(typo).unwrap();
+                assert_eq!(
+                    ShortId::parse_checked(&typo),
+                    Err(DecodeError::InvalidCheckCharacter),
+                    "{} should be rejected",
+                    typo
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_detects_every_adjacent_transposition() {
+        // Every pair of distinct characters, swapped at every position including the check
+        // character
+        let alphabet: Vec<u8> = ALPHABET.bytes().collect();
+        for (i, &a) in alphabet.iter().enumerate() {
+            for &b in &alphabet[i + 1..] {
+                let id = ShortId(String::from_utf8(vec![a, b, a, b]).unwrap());
+                let checked = id.to_checked_string().into_bytes();
+                for position in 0..checked.len() - 1 {
+                    let mut typo = checked.clone();
+                    typo.swap(position, position + 1);
+                    if typo == checked {
+                        continue;
+                    }
+                    assert_ne!(
+                        interim(core::str::from_utf8(&typo).unwrap()),
+                        Some(0),
+                        "swap at {} in {:?} should be detected",
+                        position,
+                        core::str::from_utf8(&checked).unwrap()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_characters_outside_the_alphabet() {
+        assert_eq!(
+            ShortId::parse_checked("not valid!"),
+            Err(DecodeError::InvalidEncoding)
+        );
+        assert_eq!(
+            ShortId::parse_checked(""),
+            Err(DecodeError::InvalidCheckCharacter)
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_id_with_valid_check_character() {
+        // Five characters never encode a whole number of bytes
+        let checked = ShortId("-----".to_string()).to_checked_string();
+        assert_eq!(
+            ShortId::parse_checked(&checked),
+            Err(DecodeError::InvalidEncoding)
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Sources of the current time.
+
+use std::time::SystemTime;
+
+/// Source of the current time for the stateful generators.
+///
+/// [`SystemClock`] reads the system clock. Tests can substitute a fixed or manually
+/// advanced clock; any `Fn() -> SystemTime` closure is a clock.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{OrderedGenerator, OrderedLayout};
+/// use std::time::{Duration, SystemTime};
+///
+/// let frozen = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 10).with_clock(move || frozen);
+///
+/// let id = generator.generate().unwrap();
+/// assert_eq!(OrderedLayout::DEFAULT.timestamp(id.as_str()).unwrap(), frozen);
+/// ```
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The system clock, via [`SystemTime::now()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl<F: Fn() -> SystemTime> Clock for F {
+    fn now(&self) -> SystemTime {
+        self()
+    }
+}
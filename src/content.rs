@@ -0,0 +1,292 @@
+//! Content-addressed, deterministic IDs.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use sha2::{Digest, Sha256};
+
+use crate::{encode, ShortId, MAX_BYTES};
+
+/// Hash algorithm used to derive content-addressed IDs.
+///
+/// Both algorithms produce 32-byte digests, which are truncated to the requested ID
+/// length.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ContentHash {
+    /// SHA-256 (the default).
+    #[default]
+    Sha256,
+    /// BLAKE3. Requires the `blake3` feature.
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+/// Domain byte hashed before plain content.
+const PLAIN: u8 = 0x00;
+
+/// Domain byte hashed before namespaced content.
+const NAMESPACED: u8 = 0x01;
+
+impl ContentHash {
+    /// Hashes a domain byte, the optional namespace and then the content.
+    ///
+    /// Plain content is hashed as `0x00 || content`, and namespaced content as
+    /// `0x01 || len || namespace || content` with the namespace length as a big-endian
+    /// `u64`. The domain byte keeps plain content from colliding with a namespaced ID, and
+    /// the length prefix means moving bytes between the namespace and the content always
+    /// changes the digest.
+    fn digest(self, namespace: Option<&str>, content: &[u8]) -> [u8; 32] {
+        let prefix = namespace.map(|namespace| (namespace.len() as u64).to_be_bytes());
+        let domain = if namespace.is_some() {
+            NAMESPACED
+        } else {
+            PLAIN
+        };
+        match self {
+            ContentHash::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update([domain]);
+                if let (Some(prefix), Some(namespace)) = (prefix, namespace) {
+                    hasher.update(prefix);
+                    hasher.update(namespace.as_bytes());
+                }
+                hasher.update(content);
+                hasher.finalize().into()
+            }
+            #[cfg(feature = "blake3")]
+            ContentHash::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update(&[domain]);
+                if let (Some(prefix), Some(namespace)) = (prefix, namespace) {
+                    hasher.update(&prefix);
+                    hasher.update(namespace.as_bytes());
+                }
+                hasher.update(content);
+                hasher.finalize().into()
+            }
+        }
+    }
+}
+
+/// Internal helper: derives a content-addressed ID with the specified number of bytes.
+///
+/// # Panics
+///
+/// Panics if `num_bytes` is 0 or exceeds `MAX_BYTES`.
+fn generate_content_id(
+    hash: ContentHash,
+    namespace: Option<&str>,
+    content: &[u8],
+    num_bytes: usize,
+) -> String {
+    assert!(num_bytes > 0, "num_bytes must be greater than 0");
+    assert!(
+        num_bytes <= MAX_BYTES,
+        "num_bytes must not exceed {} (got {})",
+        MAX_BYTES,
+        num_bytes
+    );
+
+    encode(&hash.digest(namespace, content)[..num_bytes])
+}
+
+/// Derives a deterministic, URL-safe short ID from content.
+///
+/// The same content always maps to the same 14-character ID, which makes it suitable for
+/// deduplication keys, cache keys and asset names. The ID is the first 10 bytes of the
+/// SHA-256 digest of a `0x00` byte followed by `content`, encoded exactly like
+/// [`short_id()`](crate::short_id).
+///
+/// **This function requires the `content` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::short_id_from_content;
+///
+/// let id = short_id_from_content(b"hello world");
+/// assert_eq!(id.len(), 14);
+/// assert_eq!(id, short_id_from_content(b"hello world"));
+/// assert_ne!(id, short_id_from_content(b"hello world!"));
+/// ```
+pub fn short_id_from_content(content: &[u8]) -> String {
+    generate_content_id(ContentHash::Sha256, None, content, 10)
+}
+
+/// **Advanced:** Derives a deterministic short ID from content with a custom number of bytes.
+///
+/// Like [`short_id_from_content()`], but keeps the first `num_bytes` bytes of the SHA-256
+/// digest, so the output matches [`short_id_with_bytes()`](crate::short_id_with_bytes) in
+/// length.
+///
+/// **This function requires the `content` feature.**
+///
+/// # Panics
+///
+/// Panics if `num_bytes` is 0 or exceeds 32.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::short_id_from_content_with_bytes;
+///
+/// let id = short_id_from_content_with_bytes(b"logo.png", 16);
+/// assert_eq!(id.len(), 22);
+/// ```
+pub fn short_id_from_content_with_bytes(content: &[u8], num_bytes: usize) -> String {
+    generate_content_id(ContentHash::Sha256, None, content, num_bytes)
+}
+
+/// Derives a deterministic short ID from content within a namespace.
+///
+/// Analogous to UUIDv5: the same content yields different IDs in different namespaces,
+/// so unrelated uses (e.g. `"users"` and `"orders"`) never share IDs by accident.
+///
+/// **This function requires the `content` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::short_id_from_namespaced_content;
+///
+/// let user = short_id_from_namespaced_content("users", b"alice@example.com");
+/// let order = short_id_from_namespaced_content("orders", b"alice@example.com");
+/// assert_eq!(user.len(), 14);
+/// assert_ne!(user, order);
+/// ```
+pub fn short_id_from_namespaced_content(namespace: &str, content: &[u8]) -> String {
+    generate_content_id(ContentHash::Sha256, Some(namespace), content, 10)
+}
+
+/// **Advanced:** Derives a deterministic short ID with every option spelled out.
+///
+/// `hash` selects the digest algorithm, `namespace` optionally scopes the ID (see
+/// [`short_id_from_namespaced_content()`]), and `num_bytes` sets the number of digest
+/// bytes kept.
+///
+/// **This function requires the `content` feature.**
+///
+/// # Panics
+///
+/// Panics if `num_bytes` is 0 or exceeds 32.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{short_id_from_content, short_id_from_content_with, ContentHash};
+///
+/// let id = short_id_from_content_with(ContentHash::Sha256, None, b"data", 10);
+/// assert_eq!(id, short_id_from_content(b"data"));
+/// ```
+pub fn short_id_from_content_with(
+    hash: ContentHash,
+    namespace: Option<&str>,
+    content: &[u8],
+    num_bytes: usize,
+) -> String {
+    generate_content_id(hash, namespace, content, num_bytes)
+}
+
+impl ShortId {
+    /// Derives a deterministic short ID from content.
+    ///
+    /// This is equivalent to calling [`short_id_from_content()`] but returns a typed
+    /// [`ShortId`]. Requires the `content` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::ShortId;
+    ///
+    /// let id = ShortId::from_content(b"hello world");
+    /// assert_eq!(id, ShortId::from_content(b"hello world"));
+    /// ```
+    pub fn from_content(content: &[u8]) -> Self {
+        ShortId(short_id_from_content(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_id_is_truncated_sha256() {
+        let digest = Sha256::digest(b"\x00abc");
+        assert_eq!(short_id_from_content(b"abc"), encode(&digest[..10]));
+        assert_eq!(
+            short_id_from_content_with_bytes(b"abc", 32),
+            encode(&digest)
+        );
+    }
+
+    #[test]
+    fn test_content_id_is_deterministic() {
+        assert_eq!(
+            short_id_from_content(b"same"),
+            short_id_from_content(b"same")
+        );
+        assert_ne!(
+            short_id_from_content(b"same"),
+            short_id_from_content(b"other")
+        );
+    }
+
+    #[test]
+    fn test_content_id_lengths_match_random_ids() {
+        for num_bytes in [1, 6, 10, 16, 32] {
+            assert_eq!(
+                short_id_from_content_with_bytes(b"data", num_bytes).len(),
+                crate::short_id_with_bytes(num_bytes).len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_namespaces_separate_ids() {
+        let plain = short_id_from_content(b"alice");
+        let users = short_id_from_namespaced_content("users", b"alice");
+        let orders = short_id_from_namespaced_content("orders", b"alice");
+        assert_ne!(plain, users);
+        assert_ne!(users, orders);
+        assert_eq!(users, short_id_from_namespaced_content("users", b"alice"));
+    }
+
+    #[test]
+    fn test_namespace_boundary_is_unambiguous() {
+        assert_ne!(
+            short_id_from_namespaced_content("ab", b"c"),
+            short_id_from_namespaced_content("a", b"bc")
+        );
+    }
+
+    #[test]
+    fn test_plain_content_cannot_forge_namespaced_id() {
+        let forged = [&5u64.to_be_bytes()[..], b"usersalice"].concat();
+        assert_ne!(
+            short_id_from_content(&forged),
+            short_id_from_namespaced_content("users", b"alice")
+        );
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3_content_id() {
+        let id = short_id_from_content_with(ContentHash::Blake3, None, b"abc", 10);
+        assert_eq!(id, encode(&blake3::hash(b"\x00abc").as_bytes()[..10]));
+        assert_ne!(id, short_id_from_content(b"abc"));
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bytes must be greater than 0")]
+    fn test_zero_bytes_panics() {
+        short_id_from_content_with_bytes(b"data", 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bytes must not exceed 32")]
+    fn test_too_many_bytes_panics() {
+        short_id_from_content_with_bytes(b"data", 33);
+    }
+}
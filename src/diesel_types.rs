@@ -0,0 +1,172 @@
+//! Diesel support for [`ShortId`].
+//!
+//! `AsExpression` and `FromSqlRow` are derived on `ShortId` itself; this module provides
+//! the `ToSql` and `FromSql` impls behind them. Text columns and reading binary columns
+//! work with every backend. Writing a binary column binds the decoded bytes, which takes
+//! backend-specific code enabled by the `diesel-sqlite`, `diesel-postgres` and
+//! `diesel-mysql` features.
+
+use ::diesel::{
+    backend::Backend,
+    deserialize::{self, FromSql},
+    serialize::{self, Output, ToSql},
+    sql_types::{Binary, Text},
+};
+
+use crate::ShortId;
+
+impl<DB: Backend> ToSql<Text, DB> for ShortId
+where
+    str: ToSql<Text, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        self.as_str().to_sql(out)
+    }
+}
+
+impl<DB: Backend> FromSql<Text, DB> for ShortId
+where
+    String: FromSql<Text, DB>,
+{
+    /// Reads a text value, rejecting strings that are not a valid ID encoding.
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let id = String::from_sql(bytes)?;
+        ShortId::parse(&id)?;
+        Ok(ShortId(id))
+    }
+}
+
+/// Writes the bytes the ID decodes to, for backends that serialize binds to bytes.
+#[cfg(any(feature = "diesel-postgres", feature = "diesel-mysql"))]
+macro_rules! impl_write_binary {
+    ($backend:ty) => {
+        impl ToSql<Binary, $backend> for ShortId {
+            /// Writes the bytes the ID decodes to, failing if it is not a valid encoding.
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, $backend>) -> serialize::Result {
+                std::io::Write::write_all(out, &self.to_bytes()?)?;
+                Ok(serialize::IsNull::No)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "diesel-postgres")]
+impl_write_binary!(::diesel::pg::Pg);
+#[cfg(feature = "diesel-mysql")]
+impl_write_binary!(::diesel::mysql::Mysql);
+
+#[cfg(feature = "diesel-sqlite")]
+impl ToSql<Binary, ::diesel::sqlite::Sqlite> for ShortId {
+    /// Binds the bytes the ID decodes to, failing if it is not a valid encoding.
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut Output<'b, '_, ::diesel::sqlite::Sqlite>,
+    ) -> serialize::Result {
+        out.set_value(self.to_bytes()?);
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl<DB: Backend> FromSql<Binary, DB> for ShortId
+where
+    Vec<u8>: FromSql<Binary, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        Ok(ShortId::from_bytes(&Vec::<u8>::from_sql(bytes)?))
+    }
+}
+
+#[cfg(all(test, feature = "diesel-sqlite"))]
+mod tests {
+    use super::*;
+
+    use ::diesel::{connection::SimpleConnection, dsl::sql, prelude::*, sqlite::SqliteConnection};
+
+    ::diesel::table! {
+        events (id) {
+            id -> Text,
+            raw -> Binary,
+        }
+    }
+
+    fn connect() -> SqliteConnection {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        conn.batch_execute("CREATE TABLE events (id TEXT PRIMARY KEY, raw BLOB NOT NULL)")
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_text_and_binary_columns() {
+        let mut conn = connect();
+        let id = ShortId::random();
+
+        ::diesel::insert_into(events::table)
+            .values((events::id.eq(&id), events::raw.eq(&id)))
+            .execute(&mut conn)
+            .unwrap();
+
+        let (text, raw): (ShortId, ShortId) = events::table
+            .filter(events::id.eq(&id))
+            .select((events::id, events::raw))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(text, id);
+        assert_eq!(raw, id);
+
+        let len: i32 = events::table
+            .select(sql::<::diesel::sql_types::Integer>("length(raw)"))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(len, 10);
+    }
+
+    #[test]
+    fn test_rejects_invalid_text() {
+        let mut conn = connect();
+        conn.batch_execute("INSERT INTO events VALUES ('not valid!', x'00')")
+            .unwrap();
+
+        let result = events::table.select(events::id).first::<ShortId>(&mut conn);
+        assert!(matches!(
+            result,
+            Err(::diesel::result::Error::DeserializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_id_fails_to_bind_as_binary() {
+        let mut conn = connect();
+        let id = ShortId::from(String::from("not valid!"));
+
+        let result = ::diesel::insert_into(events::table)
+            .values((events::id.eq("x"), events::raw.eq(&id)))
+            .execute(&mut conn);
+        assert!(matches!(
+            result,
+            Err(::diesel::result::Error::SerializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_binary_column_keeps_ordered_ids_in_order() {
+        let mut conn = connect();
+        let mut ids = Vec::new();
+        for _ in 0..20 {
+            let id = ShortId::ordered();
+            ::diesel::insert_into(events::table)
+                .values((events::id.eq(ShortId::random()), events::raw.eq(&id)))
+                .execute(&mut conn)
+                .unwrap();
+            ids.push(id);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        let stored: Vec<ShortId> = events::table
+            .select(events::raw)
+            .order(events::raw.asc())
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(stored, ids);
+    }
+}
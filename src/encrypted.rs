@@ -0,0 +1,277 @@
+//! Ordered IDs encrypted with a block cipher.
+
+use std::time::SystemTime;
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::Aes256;
+
+use crate::{decode, encode, DecodeError, OrderedLayout, ShortId, TimestampOutOfRange};
+
+/// Number of bytes in an encrypted ordered ID: one AES block.
+const BLOCK_BYTES: usize = 16;
+
+/// Ordered IDs that look random to anyone without the key.
+///
+/// Plain ordered IDs start with their creation time, so anyone can decode when an ID was
+/// created and estimate request volume from IDs generated close together.
+/// `OrderedCipher` generates a 16-byte ordered ID and encrypts it as a single AES-256
+/// block, so the resulting 22-character ID carries no visible structure. Services holding
+/// the key can [`decrypt()`](Self::decrypt) it back into the plain ordered ID, which
+/// sorts by creation time and decodes with the [`OrderedLayout`] it was generated with.
+///
+/// Encrypted IDs do **not** sort by creation time themselves; sort by their decrypted
+/// form instead (e.g. store the plain ID internally and hand out the encrypted one).
+///
+/// **This type requires the `encrypted` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::OrderedCipher;
+///
+/// let cipher = OrderedCipher::new(&[7u8; 32]);
+///
+/// let first = cipher.generate().unwrap();
+/// let second = cipher.generate().unwrap();
+/// assert_eq!(first.as_str().len(), 22);
+///
+/// // Decrypting recovers the plain ordered IDs and their creation time
+/// let first_plain = cipher.decrypt(first.as_str()).unwrap();
+/// let created = cipher.timestamp(first.as_str()).unwrap();
+/// assert!(created <= cipher.timestamp(second.as_str()).unwrap());
+/// assert_eq!(cipher.encrypt(&first_plain).unwrap(), first.as_str());
+/// ```
+#[derive(Clone)]
+pub struct OrderedCipher {
+    cipher: Aes256,
+    layout: OrderedLayout,
+}
+
+impl OrderedCipher {
+    /// Creates a cipher from a 256-bit secret key, using the default ordered layout
+    /// (microseconds since the Unix epoch in 8 bytes, followed by 8 random bytes).
+    pub fn new(key: &[u8; 32]) -> Self {
+        OrderedCipher {
+            cipher: Aes256::new(GenericArray::from_slice(key)),
+            layout: OrderedLayout::DEFAULT,
+        }
+    }
+
+    /// Uses `layout` for the plain IDs that get encrypted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{OrderedCipher, OrderedLayout, Precision};
+    ///
+    /// let layout = OrderedLayout::new().with_precision(Precision::Milliseconds);
+    /// let cipher = OrderedCipher::new(&[7u8; 32]).with_layout(layout);
+    ///
+    /// let id = cipher.generate().unwrap();
+    /// let plain = cipher.decrypt(id.as_str()).unwrap();
+    /// assert_eq!(layout.timestamp(&plain), cipher.timestamp(id.as_str()));
+    /// ```
+    pub fn with_layout(mut self, layout: OrderedLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Returns the layout of the plain IDs.
+    pub fn layout(&self) -> &OrderedLayout {
+        &self.layout
+    }
+
+    /// Generates a new encrypted ordered ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TimestampOutOfRange`] if the system clock is before the layout's epoch, or
+    /// the timestamp does not fit in the layout's timestamp bytes.
+    pub fn generate(&self) -> Result<ShortId, TimestampOutOfRange> {
+        let mut block = [0u8; BLOCK_BYTES];
+        self.layout.fill(self.layout.now()?, &mut block);
+        self.cipher
+            .encrypt_block(GenericArray::from_mut_slice(&mut block));
+        Ok(ShortId::from_bytes(&block))
+    }
+
+    /// Encrypts a plain 16-byte ordered ID, e.g. one stored internally.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it does not decode to exactly 16 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{short_id_ordered_with_bytes, OrderedCipher};
+    ///
+    /// let cipher = OrderedCipher::new(&[7u8; 32]);
+    /// let plain = short_id_ordered_with_bytes(16);
+    ///
+    /// let public = cipher.encrypt(&plain).unwrap();
+    /// assert_eq!(cipher.decrypt(&public), Ok(plain));
+    /// ```
+    pub fn encrypt(&self, id: &str) -> Result<String, DecodeError> {
+        let mut block = read_block(id)?;
+        self.cipher
+            .encrypt_block(GenericArray::from_mut_slice(&mut block));
+        Ok(encode(&block))
+    }
+
+    /// Decrypts an encrypted ID back into the plain ordered ID.
+    ///
+    /// Decryption cannot detect a wrong key or a forged ID: any 16-byte ID decrypts to
+    /// *some* plain ID. Use [`timestamp()`](Self::timestamp) or a database lookup to
+    /// reject IDs that make no sense.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it does not decode to exactly 16 bytes.
+    pub fn decrypt(&self, id: &str) -> Result<String, DecodeError> {
+        let mut block = read_block(id)?;
+        self.cipher
+            .decrypt_block(GenericArray::from_mut_slice(&mut block));
+        Ok(encode(&block))
+    }
+
+    /// Decrypts an encrypted ID and decodes its creation time.
+    ///
+    /// The result is truncated to the layout's [`Precision`](crate::Precision).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it does not decode to exactly 16 bytes.
+    pub fn timestamp(&self, id: &str) -> Result<SystemTime, DecodeError> {
+        self.layout.timestamp(&self.decrypt(id)?)
+    }
+}
+
+impl core::fmt::Debug for OrderedCipher {
+    /// Omits the key material.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OrderedCipher")
+            .field("layout", &self.layout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Decodes an ID that must be exactly one cipher block long.
+fn read_block(id: &str) -> Result<[u8; BLOCK_BYTES], DecodeError> {
+    let bytes = decode(id)?;
+    bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| DecodeError::InvalidLength(bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Precision, SortOrder};
+
+    const KEY: [u8; 32] = [42; 32];
+
+    #[test]
+    fn test_encrypted_ids_hide_the_timestamp() {
+        let cipher = OrderedCipher::new(&KEY);
+        let ids: Vec<ShortId> = (0..100).map(|_| cipher.generate().unwrap()).collect();
+
+        // Plain ordered IDs generated together share their leading timestamp bytes;
+        // encrypted ones must not
+        let prefixes: std::collections::HashSet<&str> =
+            ids.iter().map(|id| &id.as_str()[..4]).collect();
+        assert!(prefixes.len() > 90);
+
+        for id in &ids {
+            assert_ne!(
+                OrderedLayout::DEFAULT.timestamp(id.as_str()),
+                cipher.timestamp(id.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_decrypted_ids_are_ordered() {
+        let cipher = OrderedCipher::new(&KEY);
+        let mut plain = Vec::new();
+        for _ in 0..5 {
+            plain.push(cipher.decrypt(cipher.generate().unwrap().as_str()).unwrap());
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        let mut sorted = plain.clone();
+        sorted.sort_by_key(|id| decode(id).unwrap());
+        assert_eq!(plain, sorted);
+    }
+
+    #[test]
+    fn test_timestamp_roundtrip() {
+        let cipher = OrderedCipher::new(&KEY);
+        let before = SystemTime::now();
+        let id = cipher.generate().unwrap();
+        let created = cipher.timestamp(id.as_str()).unwrap();
+        assert!(created <= SystemTime::now());
+        assert!(
+            before
+                .duration_since(created)
+                .unwrap_or_default()
+                .as_micros()
+                <= 1
+        );
+    }
+
+    #[test]
+    fn test_encrypt_inverts_decrypt() {
+        let cipher = OrderedCipher::new(&KEY);
+        let id = cipher.generate().unwrap();
+        let plain = cipher.decrypt(id.as_str()).unwrap();
+        assert_eq!(cipher.encrypt(&plain), Ok(id.as_str().to_string()));
+    }
+
+    #[test]
+    fn test_wrong_key_does_not_recover_the_plain_id() {
+        let id = OrderedCipher::new(&KEY).generate().unwrap();
+        let other = OrderedCipher::new(&[1; 32]);
+        assert_ne!(
+            other.decrypt(id.as_str()),
+            OrderedCipher::new(&KEY).decrypt(id.as_str())
+        );
+    }
+
+    #[test]
+    fn test_custom_layout() {
+        let layout = OrderedLayout::new()
+            .with_precision(Precision::Milliseconds)
+            .with_timestamp_bytes(6)
+            .with_order(SortOrder::Descending)
+            .with_buckets(4);
+        let cipher = OrderedCipher::new(&KEY).with_layout(layout);
+        let id = cipher.generate().unwrap();
+        let plain = cipher.decrypt(id.as_str()).unwrap();
+        assert!(layout.bucket(&plain).unwrap() < 4);
+        assert_eq!(cipher.timestamp(id.as_str()), layout.timestamp(&plain));
+    }
+
+    #[test]
+    fn test_rejects_ids_that_are_not_one_block() {
+        let cipher = OrderedCipher::new(&KEY);
+        assert_eq!(
+            cipher.decrypt(&crate::short_id()),
+            Err(DecodeError::InvalidLength(10))
+        );
+        assert_eq!(
+            cipher.encrypt("not valid!"),
+            Err(DecodeError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_debug_hides_key() {
+        let debug = format!("{:?}", OrderedCipher::new(&KEY));
+        assert!(debug.starts_with("OrderedCipher { layout: "));
+        assert!(!debug.contains("42"));
+    }
+}
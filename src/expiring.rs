@@ -0,0 +1,473 @@
+//! Self-expiring tokens.
+
+use std::time::{Duration, SystemTime};
+
+use rand::{rngs::OsRng, RngCore};
+
+use crate::{
+    decode, encode, Clock, DecodeError, OrderedLayout, Precision, ShortId, SystemClock, MAX_BYTES,
+};
+#[cfg(feature = "signed")]
+use crate::{SignedIdGenerator, VerifyError};
+
+/// Expiry time component: milliseconds since the Unix epoch in 6 bytes.
+const EXPIRY: OrderedLayout = OrderedLayout::new()
+    .with_precision(Precision::Milliseconds)
+    .with_timestamp_bytes(6);
+
+/// Number of bytes holding the expiry time.
+const EXPIRY_BYTES: usize = 6;
+
+/// A token that carries its own expiry time.
+///
+/// The token is an ID whose first 6 bytes hold the expiry instant (milliseconds since
+/// the Unix epoch, big-endian), followed by cryptographically secure random bytes.
+/// Validation can therefore reject stale tokens without a lookup. Since the expiry
+/// comes first, unsigned tokens stored as bytes (see [`BinaryId`](crate::BinaryId)) sort
+/// by expiry, which makes purging expired ones a range delete.
+///
+/// Tokens are created by an [`ExpiringTokenGenerator`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ExpiringToken {
+    id: ShortId,
+    expires_at: SystemTime,
+}
+
+impl ExpiringToken {
+    /// Decodes an unsigned token, without checking whether it has expired.
+    ///
+    /// Signed tokens must be checked with [`ExpiringTokenGenerator::validate()`] instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `token` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it is too short to hold an expiry time and
+    /// random bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{ExpiringToken, ExpiringTokenGenerator};
+    /// use std::time::Duration;
+    ///
+    /// let token = ExpiringTokenGenerator::new(Duration::from_secs(60)).generate();
+    ///
+    /// let decoded = ExpiringToken::decode(token.as_str()).unwrap();
+    /// assert_eq!(decoded.expires_at(), token.expires_at());
+    /// ```
+    pub fn decode(token: &str) -> Result<Self, DecodeError> {
+        let bytes = decode(token)?;
+        Ok(ExpiringToken {
+            id: ShortId(String::from(token)),
+            expires_at: read_expiry(&bytes)?,
+        })
+    }
+
+    /// Returns the token string.
+    pub fn as_str(&self) -> &str {
+        self.id.as_str()
+    }
+
+    /// Returns the token as a [`ShortId`].
+    pub fn id(&self) -> &ShortId {
+        &self.id
+    }
+
+    /// Returns the instant the token expires, truncated to milliseconds.
+    pub fn expires_at(&self) -> SystemTime {
+        self.expires_at
+    }
+
+    /// Returns `true` if the token has expired at `now`.
+    ///
+    /// A token is valid up to, but not including, its expiry instant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::ExpiringTokenGenerator;
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let token = ExpiringTokenGenerator::new(Duration::from_secs(60)).generate();
+    ///
+    /// assert!(!token.is_expired(SystemTime::now()));
+    /// assert!(token.is_expired(SystemTime::now() + Duration::from_secs(61)));
+    /// ```
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+}
+
+impl core::fmt::Display for ExpiringToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ExpiringToken> for ShortId {
+    fn from(token: ExpiringToken) -> Self {
+        token.id
+    }
+}
+
+/// Generator and validator of [`ExpiringToken`]s.
+///
+/// Each token expires a fixed time-to-live after it was generated, as read from the
+/// generator's [`Clock`]. By default tokens hold 10 random bytes after the 6-byte expiry,
+/// giving 22-character tokens.
+///
+/// With the `signed` feature, [`with_signer()`](Self::with_signer) appends an HMAC tag, so
+/// that the expiry cannot be extended by editing the token.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::ExpiringTokenGenerator;
+/// use std::time::Duration;
+///
+/// let tokens = ExpiringTokenGenerator::new(Duration::from_secs(15 * 60));
+///
+/// let token = tokens.generate();
+/// assert_eq!(token.as_str().len(), 22);
+///
+/// // Later, when the token comes back
+/// let validated = tokens.validate(token.as_str()).unwrap();
+/// assert_eq!(validated, token);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExpiringTokenGenerator<C = SystemClock> {
+    ttl: Duration,
+    random_bytes: usize,
+    clock: C,
+    #[cfg(feature = "signed")]
+    signer: Option<SignedIdGenerator>,
+}
+
+impl ExpiringTokenGenerator {
+    /// Creates a generator of tokens that expire `ttl` after they are generated, using
+    /// the system clock.
+    pub fn new(ttl: Duration) -> Self {
+        ExpiringTokenGenerator {
+            ttl,
+            random_bytes: 10,
+            clock: SystemClock,
+            #[cfg(feature = "signed")]
+            signer: None,
+        }
+    }
+}
+
+impl<C: Clock> ExpiringTokenGenerator<C> {
+    /// Replaces the clock used to compute and check expiry times.
+    pub fn with_clock<D: Clock>(self, clock: D) -> ExpiringTokenGenerator<D> {
+        ExpiringTokenGenerator {
+            ttl: self.ttl,
+            random_bytes: self.random_bytes,
+            clock,
+            #[cfg(feature = "signed")]
+            signer: self.signer,
+        }
+    }
+
+    /// Sets the number of random bytes after the expiry time (10 by default).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `random_bytes` is less than 8 or greater than 26.
+    pub fn with_random_bytes(mut self, random_bytes: usize) -> Self {
+        assert!(
+            (8..=MAX_BYTES - EXPIRY_BYTES).contains(&random_bytes),
+            "random_bytes must be between 8 and {}",
+            MAX_BYTES - EXPIRY_BYTES
+        );
+        self.random_bytes = random_bytes;
+        self
+    }
+
+    /// Signs generated tokens with `signer`, and requires a valid signature when
+    /// validating.
+    ///
+    /// **This method requires the `signed` feature.**
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{ExpiringTokenGenerator, SignedIdGenerator};
+    /// use std::time::Duration;
+    ///
+    /// let signer = SignedIdGenerator::new(b"a secret key from configuration");
+    /// let tokens = ExpiringTokenGenerator::new(Duration::from_secs(60)).with_signer(signer);
+    ///
+    /// let token = tokens.generate();
+    /// assert_eq!(token.as_str().len(), 34);
+    /// assert!(tokens.validate(token.as_str()).is_ok());
+    /// ```
+    #[cfg(feature = "signed")]
+    pub fn with_signer(mut self, signer: SignedIdGenerator) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Returns the time-to-live of generated tokens.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the clock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Generates a token expiring the time-to-live after the clock's current time.
+    ///
+    /// The expiry is truncated to milliseconds and clamped to the year 10889, the largest
+    /// instant 6 bytes of milliseconds can hold.
+    pub fn generate(&self) -> ExpiringToken {
+        let ticks = match self.clock.now().checked_add(self.ttl) {
+            Some(expires_at) => EXPIRY.ticks_at(expires_at),
+            None => EXPIRY.max_ticks(),
+        };
+
+        let mut bytes = vec![0u8; EXPIRY_BYTES + self.random_bytes];
+        EXPIRY.write_ticks(ticks, &mut bytes);
+        OsRng.fill_bytes(&mut bytes[EXPIRY_BYTES..]);
+
+        ExpiringToken {
+            id: ShortId(self.seal(&bytes)),
+            expires_at: EXPIRY.time_of(ticks),
+        }
+    }
+
+    /// Decodes a token and checks that it has not expired according to the clock.
+    ///
+    /// # Errors
+    ///
+    /// - [`TokenError::Decode`] if `token` is not a valid token string
+    /// - [`TokenError::Verify`] if the generator signs tokens and the signature is
+    ///   invalid
+    /// - [`TokenError::Expired`] if the token has expired
+    pub fn validate(&self, token: &str) -> Result<ExpiringToken, TokenError> {
+        let bytes = self.open(token)?;
+        let expires_at = read_expiry(&bytes)?;
+        let token = ExpiringToken {
+            id: ShortId(String::from(token)),
+            expires_at,
+        };
+        if token.is_expired(self.clock.now()) {
+            return Err(TokenError::Expired(expires_at));
+        }
+        Ok(token)
+    }
+
+    /// Encodes the token bytes, signing them if the generator has a signer.
+    fn seal(&self, bytes: &[u8]) -> String {
+        #[cfg(feature = "signed")]
+        if let Some(signer) = &self.signer {
+            return signer.sign_bytes(bytes);
+        }
+        encode(bytes)
+    }
+
+    /// Decodes a token string, verifying its signature if the generator has a signer.
+    fn open(&self, token: &str) -> Result<Vec<u8>, TokenError> {
+        #[cfg(feature = "signed")]
+        if let Some(signer) = &self.signer {
+            return Ok(signer.verify(token)?.to_bytes()?);
+        }
+        Ok(decode(token)?)
+    }
+}
+
+/// Reads the expiry time of a token, checking that random bytes follow it.
+fn read_expiry(bytes: &[u8]) -> Result<SystemTime, DecodeError> {
+    if bytes.len() <= EXPIRY_BYTES {
+        return Err(DecodeError::InvalidLength(bytes.len()));
+    }
+    Ok(EXPIRY.time_of(EXPIRY.read_ticks(bytes)))
+}
+
+/// Error returned when a token fails validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TokenError {
+    /// The string is not a valid token.
+    Decode(DecodeError),
+    /// The token's signature is invalid. Requires the `signed` feature.
+    #[cfg(feature = "signed")]
+    Verify(VerifyError),
+    /// The token expired at the contained instant.
+    Expired(SystemTime),
+}
+
+impl From<DecodeError> for TokenError {
+    fn from(error: DecodeError) -> Self {
+        TokenError::Decode(error)
+    }
+}
+
+#[cfg(feature = "signed")]
+impl From<VerifyError> for TokenError {
+    fn from(error: VerifyError) -> Self {
+        TokenError::Verify(error)
+    }
+}
+
+impl core::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TokenError::Decode(error) => write!(f, "{}", error),
+            #[cfg(feature = "signed")]
+            TokenError::Verify(error) => write!(f, "{}", error),
+            TokenError::Expired(expires_at) => {
+                let since_epoch = expires_at
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default();
+                write!(
+                    f,
+                    "token expired at {}ms since the Unix epoch",
+                    since_epoch.as_millis()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TokenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TokenError::Decode(error) => Some(error),
+            #[cfg(feature = "signed")]
+            TokenError::Verify(error) => Some(error),
+            TokenError::Expired(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_token_layout() {
+        let tokens = ExpiringTokenGenerator::new(Duration::from_secs(60)).with_clock(|| at(1_000));
+        let token = tokens.generate();
+
+        let bytes = token.id().to_bytes().unwrap();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(bytes[..6], 1_060_000u64.to_be_bytes()[2..]);
+        assert_eq!(token.expires_at(), at(1_060));
+    }
+
+    #[test]
+    fn test_validate_against_clock() {
+        let now = Rc::new(Cell::new(at(1_000)));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || now.get()
+        };
+        let tokens = ExpiringTokenGenerator::new(Duration::from_secs(60)).with_clock(clock);
+        let token = tokens.generate();
+
+        now.set(at(1_059));
+        assert_eq!(tokens.validate(token.as_str()), Ok(token.clone()));
+
+        now.set(at(1_060));
+        assert_eq!(
+            tokens.validate(token.as_str()),
+            Err(TokenError::Expired(at(1_060)))
+        );
+    }
+
+    #[test]
+    fn test_is_expired_boundary() {
+        let token = ExpiringTokenGenerator::new(Duration::from_secs(1))
+            .with_clock(|| at(10))
+            .generate();
+        assert!(!token.is_expired(at(10)));
+        assert!(!token.is_expired(at(11) - Duration::from_millis(1)));
+        assert!(token.is_expired(at(11)));
+    }
+
+    #[test]
+    fn test_expiry_is_truncated_to_milliseconds() {
+        let tokens = ExpiringTokenGenerator::new(Duration::from_micros(1_999)).with_clock(|| at(5));
+        assert_eq!(
+            tokens.generate().expires_at(),
+            at(5) + Duration::from_millis(1)
+        );
+    }
+
+    #[test]
+    fn test_huge_ttl_is_clamped() {
+        let last = EXPIRY.time_of(EXPIRY.max_ticks());
+        for ttl in [Duration::from_secs(1 << 50), Duration::MAX] {
+            let token = ExpiringTokenGenerator::new(ttl)
+                .with_clock(|| at(1_000))
+                .generate();
+            assert_eq!(token.expires_at(), last);
+            assert_eq!(token.id().to_bytes().unwrap()[..6], [0xff; 6]);
+        }
+    }
+
+    #[test]
+    fn test_tokens_sort_by_expiry() {
+        let short = ExpiringTokenGenerator::new(Duration::from_secs(60)).generate();
+        let long = ExpiringTokenGenerator::new(Duration::from_secs(3_600)).generate();
+        assert!(decode(short.as_str()).unwrap() < decode(long.as_str()).unwrap());
+    }
+
+    #[test]
+    fn test_custom_random_bytes() {
+        let token = ExpiringTokenGenerator::new(Duration::from_secs(60))
+            .with_random_bytes(26)
+            .generate();
+        assert_eq!(token.id().to_bytes().unwrap().len(), 32);
+        assert_eq!(ExpiringToken::decode(token.as_str()), Ok(token));
+    }
+
+    #[test]
+    fn test_rejects_malformed_tokens() {
+        let tokens = ExpiringTokenGenerator::new(Duration::from_secs(60));
+        assert_eq!(
+            tokens.validate("not valid!"),
+            Err(TokenError::Decode(DecodeError::InvalidEncoding))
+        );
+        assert_eq!(
+            tokens.validate(&encode(&[0xff; 6])),
+            Err(TokenError::Decode(DecodeError::InvalidLength(6)))
+        );
+    }
+
+    #[cfg(feature = "signed")]
+    #[test]
+    fn test_signed_tokens_cannot_be_extended() {
+        let tokens = ExpiringTokenGenerator::new(Duration::from_secs(60))
+            .with_signer(SignedIdGenerator::new(b"key"));
+        let token = tokens.generate();
+        assert!(tokens.validate(token.as_str()).is_ok());
+
+        // Push the expiry into the far future by rewriting its first byte
+        let mut bytes = token.id().to_bytes().unwrap();
+        bytes[1] = 0xff;
+        assert_eq!(
+            tokens.validate(&encode(&bytes)),
+            Err(TokenError::Verify(VerifyError::InvalidSignature))
+        );
+
+        // Unsigned validation of a signed token fails too
+        let unsigned = ExpiringTokenGenerator::new(Duration::from_secs(60));
+        assert!(unsigned.validate(token.as_str()).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "random_bytes must be between 8 and 26")]
+    fn test_too_few_random_bytes_panics() {
+        ExpiringTokenGenerator::new(Duration::from_secs(60)).with_random_bytes(4);
+    }
+}
@@ -0,0 +1,470 @@
+//! Hybrid logical clock (HLC) ordered IDs.
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use rand::{rngs::OsRng, RngCore};
+
+use crate::{DecodeError, OrderedLayout, Precision, ShortId, MAX_BYTES};
+
+/// Physical time component: milliseconds since the Unix epoch in 6 bytes.
+const PHYSICAL: OrderedLayout = OrderedLayout::new()
+    .with_precision(Precision::Milliseconds)
+    .with_timestamp_bytes(6);
+
+/// Number of bytes holding the physical time and logical counter.
+const CLOCK_BYTES: usize = 8;
+
+/// How far ahead of the local clock an observed ID may be unless configured otherwise.
+const DEFAULT_MAX_OFFSET: Duration = Duration::from_secs(60);
+
+/// The hybrid logical clock reading embedded in an HLC ID.
+///
+/// Readings compare by physical time first and logical counter second, which is the
+/// same order as the bytes of the IDs they were decoded from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HlcTimestamp {
+    /// The physical (wall clock) component, in milliseconds.
+    pub physical: SystemTime,
+    /// The logical counter, distinguishing events within the same physical millisecond.
+    pub logical: u16,
+}
+
+impl HlcTimestamp {
+    /// Decodes the clock reading of an ID generated by an [`HlcGenerator`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it is shorter than 8 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{HlcGenerator, HlcTimestamp};
+    ///
+    /// let generator = HlcGenerator::new(10);
+    /// let id = generator.generate();
+    ///
+    /// let reading = HlcTimestamp::decode(id.as_str()).unwrap();
+    /// assert!(reading.physical <= std::time::SystemTime::now());
+    /// ```
+    pub fn decode(id: &str) -> Result<Self, DecodeError> {
+        let bytes = crate::decode(id)?;
+        if bytes.len() < CLOCK_BYTES {
+            return Err(DecodeError::InvalidLength(bytes.len()));
+        }
+        let (physical, logical) = read_clock(&bytes);
+        Ok(HlcTimestamp {
+            physical: PHYSICAL.time_of(physical),
+            logical,
+        })
+    }
+}
+
+/// Generator for IDs ordered by a hybrid logical clock.
+///
+/// Wall clocks on different machines disagree, so IDs generated on one node after
+/// receiving a message can still sort before the message's own ID. A hybrid logical
+/// clock fixes this by combining the physical time with a logical counter: calling
+/// [`observe()`](Self::observe) with an ID received from another node advances the local
+/// clock past it, so every ID generated afterwards sorts after the received one.
+///
+/// A single ID stamped far in the future would drag the clock along with it, so
+/// `observe()` rejects IDs more than a maximum offset (one minute by default, see
+/// [`with_max_offset()`](Self::with_max_offset)) ahead of the local system clock.
+///
+/// Each ID is laid out as:
+///
+/// - 6 bytes: physical time, milliseconds since the Unix epoch (big-endian)
+/// - 2 bytes: logical counter (big-endian)
+/// - remaining bytes: cryptographically secure random data, to keep IDs from different
+///   nodes with the same clock reading unique
+///
+/// The clock never moves backwards, even if the system clock does, so generating an ID
+/// cannot fail. The generator keeps its state behind a mutex, so a single instance can
+/// be shared between threads (e.g. behind an `Arc`).
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{BinaryId, HlcGenerator};
+///
+/// let node_a = HlcGenerator::new(12);
+/// let node_b = HlcGenerator::new(12);
+///
+/// // Node A sends a message carrying its ID to node B
+/// let message_id = node_a.generate();
+/// node_b.observe(&message_id).unwrap();
+///
+/// // Anything node B generates afterwards sorts after the message
+/// let reply_id = node_b.generate();
+/// assert!(BinaryId::from(reply_id) > BinaryId::from(message_id));
+/// ```
+#[derive(Debug)]
+pub struct HlcGenerator {
+    num_bytes: usize,
+    max_offset: Duration,
+    state: Mutex<HlcState>,
+}
+
+/// The latest clock reading of a generator.
+#[derive(Debug, Default)]
+struct HlcState {
+    physical: u64,
+    logical: u16,
+}
+
+impl HlcState {
+    /// Advances the clock for a local event at wall-clock time `now`.
+    fn tick(&mut self, now: u64) {
+        if now > self.physical {
+            self.physical = now;
+            self.logical = 0;
+        } else {
+            self.advance(self.physical, self.logical);
+        }
+    }
+
+    /// Advances the clock for receiving a message stamped with `physical` and `logical`
+    /// at wall-clock time `now`.
+    fn receive(&mut self, now: u64, physical: u64, logical: u16) {
+        let latest = now.max(self.physical).max(physical);
+        if latest == self.physical && latest == physical {
+            self.advance(latest, self.logical.max(logical));
+        } else if latest == self.physical {
+            self.advance(latest, self.logical);
+        } else if latest == physical {
+            self.advance(latest, logical);
+        } else {
+            self.physical = latest;
+            self.logical = 0;
+        }
+    }
+
+    /// Moves the clock to the reading right after `physical` and `logical`.
+    ///
+    /// At the last millisecond the 6 physical bytes can hold, the clock stops at its final
+    /// reading rather than wrapping around to the Unix epoch.
+    fn advance(&mut self, physical: u64, logical: u16) {
+        if let Some(next) = logical.checked_add(1) {
+            self.physical = physical;
+            self.logical = next;
+        } else if physical < PHYSICAL.max_ticks() {
+            // The logical counter is exhausted: borrow the next millisecond
+            self.physical = physical + 1;
+            self.logical = 0;
+        } else {
+            self.physical = physical;
+            self.logical = logical;
+        }
+    }
+}
+
+impl HlcGenerator {
+    /// Creates a generator of `num_bytes`-byte IDs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is less than 8 or exceeds 32.
+    pub fn new(num_bytes: usize) -> Self {
+        assert!(
+            num_bytes >= CLOCK_BYTES,
+            "num_bytes must be at least {} for HLC IDs (got {})",
+            CLOCK_BYTES,
+            num_bytes
+        );
+        assert!(
+            num_bytes <= MAX_BYTES,
+            "num_bytes must not exceed {} (got {})",
+            MAX_BYTES,
+            num_bytes
+        );
+        HlcGenerator {
+            num_bytes,
+            max_offset: DEFAULT_MAX_OFFSET,
+            state: Mutex::new(HlcState::default()),
+        }
+    }
+
+    /// Sets how far ahead of the local system clock an ID passed to
+    /// [`observe()`](Self::observe) may be (one minute by default).
+    pub fn with_max_offset(mut self, max_offset: Duration) -> Self {
+        self.max_offset = max_offset;
+        self
+    }
+
+    /// Returns how far ahead of the local system clock an observed ID may be.
+    pub fn max_offset(&self) -> Duration {
+        self.max_offset
+    }
+
+    /// Generates the next ID.
+    ///
+    /// The ID sorts after every ID previously generated or observed by this generator.
+    pub fn generate(&self) -> ShortId {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.tick(PHYSICAL.ticks_at(SystemTime::now()));
+
+        let mut bytes = vec![0u8; self.num_bytes];
+        write_clock(state.physical, state.logical, &mut bytes);
+        OsRng.fill_bytes(&mut bytes[CLOCK_BYTES..]);
+
+        ShortId::from_bytes(&bytes)
+    }
+
+    /// Advances the clock past an ID received from another node.
+    ///
+    /// # Errors
+    ///
+    /// The clock is left unchanged if observing fails:
+    ///
+    /// - [`ObserveError::Decode`] if `id` is not an HLC ID
+    /// - [`ObserveError::TooFarAhead`] if the physical time of `id` is more than the
+    ///   maximum offset ahead of the local system clock
+    pub fn observe(&self, id: &ShortId) -> Result<(), ObserveError> {
+        let bytes = id.to_bytes()?;
+        if bytes.len() < CLOCK_BYTES {
+            return Err(DecodeError::InvalidLength(bytes.len()).into());
+        }
+        let (physical, logical) = read_clock(&bytes);
+
+        let now = SystemTime::now();
+        let limit = match now.checked_add(self.max_offset) {
+            Some(limit) => PHYSICAL.ticks_at(limit),
+            None => PHYSICAL.max_ticks(),
+        };
+        if physical > limit {
+            return Err(ObserveError::TooFarAhead(PHYSICAL.time_of(physical)));
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.receive(PHYSICAL.ticks_at(now), physical, logical);
+        Ok(())
+    }
+
+    /// Returns the current clock reading without generating an ID.
+    pub fn last(&self) -> HlcTimestamp {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        HlcTimestamp {
+            physical: PHYSICAL.time_of(state.physical),
+            logical: state.logical,
+        }
+    }
+}
+
+impl Default for HlcGenerator {
+    /// Creates a generator of 10-byte (14-character) IDs.
+    fn default() -> Self {
+        HlcGenerator::new(10)
+    }
+}
+
+/// Error returned when [`HlcGenerator::observe()`] rejects an ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ObserveError {
+    /// The ID is not a valid HLC ID.
+    Decode(DecodeError),
+    /// The ID's physical time, contained here, is further ahead of the local clock than
+    /// the generator's maximum offset.
+    TooFarAhead(SystemTime),
+}
+
+impl From<DecodeError> for ObserveError {
+    fn from(error: DecodeError) -> Self {
+        ObserveError::Decode(error)
+    }
+}
+
+impl core::fmt::Display for ObserveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ObserveError::Decode(error) => write!(f, "{}", error),
+            ObserveError::TooFarAhead(physical) => {
+                let since_epoch = physical
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default();
+                write!(
+                    f,
+                    "ID clock reading at {}ms since the Unix epoch is too far ahead of the local clock",
+                    since_epoch.as_millis()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObserveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObserveError::Decode(error) => Some(error),
+            ObserveError::TooFarAhead(_) => None,
+        }
+    }
+}
+
+/// Writes the physical time and logical counter to the front of `bytes`.
+fn write_clock(physical: u64, logical: u16, bytes: &mut [u8]) {
+    PHYSICAL.write_ticks(physical, bytes);
+    bytes[6..CLOCK_BYTES].copy_from_slice(&logical.to_be_bytes());
+}
+
+/// Reads the physical time and logical counter from the front of `bytes`.
+fn read_clock(bytes: &[u8]) -> (u64, u16) {
+    (
+        PHYSICAL.read_ticks(bytes),
+        u16::from_be_bytes([bytes[6], bytes[7]]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::BinaryId;
+
+    #[test]
+    fn test_ids_are_strictly_increasing() {
+        let generator = HlcGenerator::default();
+        let ids: Vec<BinaryId> = (0..10_000)
+            .map(|_| BinaryId::from(generator.generate()))
+            .collect();
+        for pair in ids.windows(2) {
+            assert!(
+                pair[0] < pair[1],
+                "{} should sort before {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    /// Builds an HLC ID carrying the given clock reading.
+    fn stamped(physical: u64, logical: u16) -> ShortId {
+        let mut bytes = [0u8; 10];
+        write_clock(physical, logical, &mut bytes);
+        ShortId::from_bytes(&bytes)
+    }
+
+    #[test]
+    fn test_observe_remote_clock_ahead() {
+        let remote = HlcGenerator::default();
+        remote.state.lock().unwrap().physical = PHYSICAL.now().unwrap() + 5_000;
+        let message = remote.generate();
+
+        let local = HlcGenerator::default();
+        local.observe(&message).unwrap();
+        let reply = local.generate();
+        assert!(BinaryId::from(reply.clone()) > BinaryId::from(message.clone()));
+
+        let sent = HlcTimestamp::decode(message.as_str()).unwrap();
+        let received = HlcTimestamp::decode(reply.as_str()).unwrap();
+        assert_eq!(received.physical, sent.physical);
+        assert_eq!(received.logical, sent.logical + 2);
+    }
+
+    #[test]
+    fn test_observe_remote_clock_behind() {
+        let remote = HlcGenerator::default();
+        let message = remote.generate();
+
+        let local = HlcGenerator::default();
+        local.state.lock().unwrap().physical = PHYSICAL.now().unwrap() + 5_000;
+        local.observe(&message).unwrap();
+
+        let reply = local.generate();
+        assert!(BinaryId::from(reply.clone()) > BinaryId::from(message.clone()));
+        assert_eq!(local.last(), HlcTimestamp::decode(reply.as_str()).unwrap());
+    }
+
+    #[test]
+    fn test_logical_counter_overflow_borrows_next_millisecond() {
+        let mut state = HlcState {
+            physical: 10,
+            logical: u16::MAX,
+        };
+        state.tick(10);
+        assert_eq!((state.physical, state.logical), (11, 0));
+
+        state.receive(5, 11, u16::MAX);
+        assert_eq!((state.physical, state.logical), (12, 0));
+    }
+
+    #[test]
+    fn test_logical_counter_overflow_at_last_millisecond() {
+        let last = PHYSICAL.max_ticks();
+        let mut state = HlcState {
+            physical: last,
+            logical: u16::MAX - 1,
+        };
+        state.tick(0);
+        assert_eq!((state.physical, state.logical), (last, u16::MAX));
+
+        // No millisecond left to borrow: the clock stays put instead of wrapping to 0
+        state.tick(0);
+        assert_eq!((state.physical, state.logical), (last, u16::MAX));
+        state.receive(0, last, u16::MAX);
+        assert_eq!((state.physical, state.logical), (last, u16::MAX));
+
+        let mut bytes = [0u8; 8];
+        write_clock(state.physical, state.logical, &mut bytes);
+        assert_eq!(bytes, [0xff; 8]);
+    }
+
+    #[test]
+    fn test_observe_rejects_ids_too_far_ahead() {
+        let generator = HlcGenerator::default();
+        let ahead = PHYSICAL.now().unwrap() + 120_000;
+        assert_eq!(
+            generator.observe(&stamped(ahead, 0)),
+            Err(ObserveError::TooFarAhead(PHYSICAL.time_of(ahead)))
+        );
+        assert_eq!(
+            generator.observe(&stamped(PHYSICAL.max_ticks(), u16::MAX)),
+            Err(ObserveError::TooFarAhead(
+                PHYSICAL.time_of(PHYSICAL.max_ticks())
+            ))
+        );
+        assert_eq!(generator.last().logical, 0);
+
+        // Within the maximum offset, the clock follows the remote reading
+        let generator = generator.with_max_offset(Duration::from_secs(300));
+        generator.observe(&stamped(ahead, 7)).unwrap();
+        assert_eq!(
+            generator.last(),
+            HlcTimestamp {
+                physical: PHYSICAL.time_of(ahead),
+                logical: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn test_clock_does_not_follow_system_clock_backwards() {
+        let generator = HlcGenerator::default();
+        let future = PHYSICAL.now().unwrap() + 5_000;
+        generator.state.lock().unwrap().physical = future;
+
+        let reading = HlcTimestamp::decode(generator.generate().as_str()).unwrap();
+        assert_eq!(reading.physical, PHYSICAL.time_of(future));
+        assert_eq!(reading.logical, 1);
+    }
+
+    #[test]
+    fn test_observe_rejects_short_ids() {
+        let generator = HlcGenerator::default();
+        let id = ShortId::from(crate::short_id_with_bytes(6));
+        assert_eq!(
+            generator.observe(&id),
+            Err(ObserveError::Decode(DecodeError::InvalidLength(6)))
+        );
+        assert_eq!(generator.last().logical, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bytes must be at least 8 for HLC IDs")]
+    fn test_too_few_bytes_panics() {
+        HlcGenerator::new(7);
+    }
+}
@@ -9,7 +9,7 @@
 //!    user-facing tokens, test data, and log correlation.
 //!
 //! 2. **Provide an optional "ordered" variant** where IDs include a timestamp prefix,
-//!    so when you sort them as strings they roughly follow creation time.
+//!    so when you sort them by their bytes they roughly follow creation time.
 //!
 //! This crate is intentionally minimal - no configuration, no custom alphabets, no complex API.
 //!
@@ -85,10 +85,70 @@
 //! **Important:** Using fewer bytes significantly increases collision probability. For most users,
 //! the default [`short_id()`] and [`short_id_ordered()`] functions are recommended.
 //!
+//! # Advanced: Ordered ID Layouts
+//!
+//! By default, ordered IDs spend 8 bytes on a microsecond timestamp counted from the Unix epoch.
+//! An [`OrderedLayout`] trades timestamp precision and range for randomness at the same length:
+//!
+//! ```
+//! use short_id::{OrderedLayout, Precision};
+//! use std::time::Duration;
+//!
+//! // Milliseconds since 2020-01-01 in 6 bytes after a tag byte, leaving 3 random bytes in a
+//! // 14-character ID
+//! const LAYOUT: OrderedLayout = OrderedLayout::new()
+//!     .with_precision(Precision::Milliseconds)
+//!     .with_timestamp_bytes(6)
+//!     .with_epoch(Duration::from_secs(1_577_836_800));
+//!
+//! let id = LAYOUT.generate(10).unwrap();
+//! assert_eq!(id.len(), 14);
+//!
+//! // The same layout decodes the creation time back out of the ID
+//! let created = LAYOUT.timestamp(&id).unwrap();
+//! assert!(created <= std::time::SystemTime::now());
+//! ```
+//!
 //! # Features
 //!
-//! - **`std`** (enabled by default): Enables [`short_id_ordered()`] and [`short_id_ordered_with_bytes()`]
-//!   which need `std::time::SystemTime`
+//! - **`std`** (enabled by default): Enables [`short_id_ordered()`], [`short_id_ordered_with_bytes()`]
+//!   and [`OrderedLayout`], which need `std::time::SystemTime`
+//! - **`content`**: Enables deterministic, content-addressed IDs such as
+//!   `short_id_from_content()` (SHA-256)
+//! - **`blake3`**: Adds BLAKE3 as a content hash (implies `content`)
+//! - **`encrypted`**: Enables `OrderedCipher`, which encrypts ordered IDs so they don't
+//!   reveal their creation time (implies `std`)
+//! - **`signed`**: Enables `SignedIdGenerator`, which appends an HMAC tag so forged or
+//!   mutated IDs can be rejected
+//! - **`secret`**: Enables `SecretId`, a redacted, zeroized, constant-time-compared ID for
+//!   session tokens, and `TokenDigest` / `StoredToken` for storing tokens hashed
+//! - **`obfuscate`**: Enables `IdObfuscator`, a keyed, reversible mapping from `u64`
+//!   database keys to IDs
+//! - **`sqlx`**: Implements `sqlx`'s `Type`, `Encode` and `Decode` for [`ShortId`] (as
+//!   text) and [`BinaryId`] (as raw bytes), for any database `sqlx` supports (implies
+//!   `std`). **`sqlx-sqlite`** and **`sqlx-postgres`** also enable the `sqlx` driver
+//! - **`diesel`**: Implements Diesel's `AsExpression`, `FromSqlRow`, `ToSql` and `FromSql`
+//!   for [`ShortId`] with the `Text` and `Binary` SQL types (implies `std`). Writing
+//!   `Binary` columns also needs the feature for the backend: **`diesel-sqlite`**,
+//!   **`diesel-postgres`** or **`diesel-mysql`**
+//! - **`rusqlite`**: Implements `rusqlite`'s `ToSql` and `FromSql` for [`ShortId`] (as
+//!   `TEXT`) and [`BinaryId`] (as a `BLOB`), rejecting malformed stored values (implies
+//!   `std`). **`rusqlite-bundled`** also compiles SQLite into the build
+//! - **`postgres`**: Implements `postgres-types`' `ToSql` and `FromSql` for [`ShortId`],
+//!   accepting `text`, `varchar` and `bytea`, for `tokio-postgres` and `postgres` (implies
+//!   `std`)
+//! - **`bson`**: Converts [`ShortId`] to and from BSON strings, and [`BinaryId`] to and
+//!   from BSON binary data with a user-defined subtype (implies `std`)
+//! - **`prost`**: Adds the `short_id.v1.ShortId` Protocol Buffers message as
+//!   `proto::v1::ShortId`, carrying an ID as bytes, with conversions to and from [`ShortId`]
+//! - **`schemars`** / **`utoipa`**: Implement `JsonSchema` and `ToSchema` for [`ShortId`],
+//!   describing its pattern, length and an example, and add `IdFormat` for describing
+//!   prefixed or fixed-length ID newtypes (imply `std`)
+//! - **`axum`**: Extracts a [`ShortId`] from a path parameter in `axum` handlers,
+//!   rejecting invalid IDs with a `400 Bad Request` (implies `std`)
+//! - **`tower`**: Adds `RequestIdLayer`, middleware that gives every request an ordered
+//!   [`ShortId`] from a valid `x-request-id` header or a new one, and `MakeShortRequestId`
+//!   for `tower-http`'s request ID middleware (implies `std`)
 //!
 //! For `no_std` environments with `alloc`:
 //!
@@ -108,7 +168,7 @@ extern crate alloc;
 use alloc::string::String;
 
 #[cfg(not(feature = "std"))]
-use alloc::vec;
+use alloc::{vec, vec::Vec};
 
 #[cfg(feature = "std")]
 use std::vec;
@@ -116,11 +176,161 @@ use std::vec;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use rand::{rngs::OsRng, RngCore};
 
+#[cfg(feature = "axum")]
+mod axum;
+mod bech32;
+mod binary;
+#[cfg(feature = "bson")]
+mod bson;
+mod check;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "content")]
+mod content;
+// Not named `diesel`: the derives on `ShortId` refer to the `diesel` crate by that name
+#[cfg(feature = "diesel")]
+mod diesel_types;
+#[cfg(feature = "encrypted")]
+mod encrypted;
+#[cfg(feature = "std")]
+mod expiring;
+#[cfg(feature = "std")]
+mod hlc;
+#[cfg(feature = "obfuscate")]
+mod obfuscate;
+#[cfg(feature = "std")]
+mod ordered;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "prost")]
+pub mod proto;
+#[cfg(feature = "rusqlite")]
+mod rusqlite;
+#[cfg(any(feature = "schemars", feature = "utoipa"))]
+mod schema;
+#[cfg(feature = "secret")]
+mod secret;
+mod secret_token;
+#[cfg(feature = "signed")]
+mod signed;
+#[cfg(feature = "std")]
+mod snowflake;
+#[cfg(feature = "sqlx")]
+mod sqlx;
+#[cfg(feature = "secret")]
+mod stored;
+#[cfg(feature = "tower")]
+mod tower;
+
+#[cfg(feature = "axum")]
+pub use axum::IdRejection;
+#[cfg(feature = "std")]
+pub use bech32::short_id_ordered_bech32;
+pub use bech32::{short_id_bech32, Bech32Error};
+pub use binary::BinaryId;
+#[cfg(feature = "bson")]
+pub use bson::BsonError;
+pub use check::short_id_checked;
+#[cfg(feature = "std")]
+pub use clock::{Clock, SystemClock};
+#[cfg(feature = "content")]
+pub use content::{
+    short_id_from_content, short_id_from_content_with, short_id_from_content_with_bytes,
+    short_id_from_namespaced_content, ContentHash,
+};
+#[cfg(feature = "encrypted")]
+pub use encrypted::OrderedCipher;
+#[cfg(feature = "std")]
+pub use expiring::{ExpiringToken, ExpiringTokenGenerator, TokenError};
+#[cfg(feature = "std")]
+pub use hlc::{HlcGenerator, HlcTimestamp, ObserveError};
+#[cfg(feature = "obfuscate")]
+pub use obfuscate::IdObfuscator;
+#[cfg(feature = "std")]
+pub use ordered::{
+    ClockRegression, GenerateError, OrderedGenerator, OrderedLayout, Precision, RegressionPolicy,
+    SortOrder, TimestampOutOfRange,
+};
+#[cfg(any(feature = "schemars", feature = "utoipa"))]
+pub use schema::IdFormat;
+#[cfg(feature = "secret")]
+pub use secret::SecretId;
+pub use secret_token::{SecretToken, SecretTokenError};
+#[cfg(feature = "signed")]
+pub use signed::{SignedIdGenerator, VerifyError};
+#[cfg(feature = "std")]
+pub use snowflake::{SnowflakeGenerator, SnowflakeLayout, SnowflakeParts};
+#[cfg(feature = "secret")]
+pub use stored::{StoredToken, TokenDigest};
+#[cfg(feature = "tower")]
+pub use tower::{MakeShortRequestId, RequestIdFuture, RequestIdLayer, RequestIdService};
+
 /// Maximum number of random bytes allowed for custom-length ID generation.
 ///
 /// This limit prevents excessive memory allocation and ensures reasonable ID sizes.
 const MAX_BYTES: usize = 32;
 
+/// The base64url characters (RFC 4648 §5), in the order of the 6-bit values they encode.
+const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Internal helper: encodes raw bytes into an ID string.
+fn encode(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Internal helper: decodes an ID string back into its raw bytes.
+fn decode(id: &str) -> Result<Vec<u8>, DecodeError> {
+    URL_SAFE_NO_PAD
+        .decode(id)
+        .map_err(|_| DecodeError::InvalidEncoding)
+}
+
+/// Error returned when an ID string cannot be decoded.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{DecodeError, ShortId};
+///
+/// let id = ShortId::from(String::from("not valid!"));
+/// assert_eq!(id.to_bytes(), Err(DecodeError::InvalidEncoding));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The string contains characters outside the ID alphabet, or has a length
+    /// that no byte sequence encodes to.
+    InvalidEncoding,
+    /// The string decodes to a number of bytes the expected layout cannot hold.
+    InvalidLength(usize),
+    /// The check character does not match the rest of the string, which was most
+    /// likely mistyped. See [`ShortId::parse_checked()`].
+    InvalidCheckCharacter,
+    /// The ordered ID's layout tag cannot be decoded with the given layout, e.g. because
+    /// the ID counts from a custom epoch the layout does not set.
+    LayoutMismatch,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::InvalidEncoding => write!(f, "invalid short ID encoding"),
+            DecodeError::InvalidLength(len) => {
+                write!(f, "invalid short ID length ({} bytes)", len)
+            }
+            DecodeError::InvalidCheckCharacter => {
+                write!(f, "short ID check character does not match")
+            }
+            DecodeError::LayoutMismatch => {
+                write!(f, "short ID was generated with a different ordered layout")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
 /// Convenience macro for generating a random short ID.
 ///
 /// This macro simply calls [`short_id()`] and is provided for ergonomics.
@@ -177,7 +387,7 @@ fn generate_random_id(num_bytes: usize) -> String {
 
     let mut bytes = vec![0u8; num_bytes];
     OsRng.fill_bytes(&mut bytes);
-    URL_SAFE_NO_PAD.encode(&bytes)
+    encode(&bytes)
 }
 
 /// Generates a random, URL-safe short ID.
@@ -241,35 +451,17 @@ pub fn short_id() -> String {
 
 /// Internal helper: generates a time-ordered ID with the specified number of bytes.
 ///
-/// Uses 8 bytes for timestamp and fills the remaining bytes with random data.
+/// Uses the default [`OrderedLayout`]: 8 bytes of microsecond timestamp, with the
+/// remaining bytes filled with random data.
 ///
 /// # Panics
 ///
 /// Panics if `num_bytes` is less than 8, is 0, or exceeds `MAX_BYTES`.
 #[cfg(feature = "std")]
 fn generate_ordered_id(num_bytes: usize) -> String {
-    assert!(
-        num_bytes >= 8,
-        "num_bytes must be at least 8 for ordered IDs (got {})",
-        num_bytes
-    );
-    assert!(
-        num_bytes <= MAX_BYTES,
-        "num_bytes must not exceed {} (got {})",
-        MAX_BYTES,
-        num_bytes
-    );
-
-    let timestamp_us = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
+    OrderedLayout::DEFAULT
+        .generate(num_bytes)
         .expect("system time before Unix epoch")
-        .as_micros() as u64;
-
-    let mut bytes = vec![0u8; num_bytes];
-    bytes[0..8].copy_from_slice(&timestamp_us.to_be_bytes());
-    OsRng.fill_bytes(&mut bytes[8..]);
-
-    URL_SAFE_NO_PAD.encode(&bytes)
 }
 
 /// Generates a time-ordered, URL-safe short ID.
@@ -728,6 +920,11 @@ mod tests {
 /// let s: String = id.into_string();
 /// ```
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Text, sql_type = diesel::sql_types::Binary)
+)]
 pub struct ShortId(String);
 
 impl ShortId {
@@ -794,6 +991,82 @@ impl ShortId {
     pub fn into_string(self) -> String {
         self.0
     }
+
+    /// Creates a short ID by encoding raw bytes.
+    ///
+    /// This is the inverse of [`ShortId::to_bytes()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::ShortId;
+    ///
+    /// let id = ShortId::from_bytes(&[0u8; 10]);
+    /// assert_eq!(id.as_str(), "AAAAAAAAAAAAAA");
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        ShortId(encode(bytes))
+    }
+
+    /// Decodes the ID back into the raw bytes it was generated from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if the ID was built from a string that is
+    /// not a valid encoding (see [`From<String>`](#impl-From<String>-for-ShortId)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::ShortId;
+    ///
+    /// let id = ShortId::random();
+    /// let bytes = id.to_bytes().unwrap();
+    /// assert_eq!(bytes.len(), 10);
+    /// assert_eq!(ShortId::from_bytes(&bytes), id);
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DecodeError> {
+        decode(&self.0)
+    }
+
+    /// Parses an ID string, checking that it is a valid encoding.
+    ///
+    /// Unlike [`From<String>`](#impl-From<String>-for-ShortId), which accepts any string,
+    /// this rejects input that [`to_bytes()`](Self::to_bytes) could not decode. Also
+    /// available through [`str::parse()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidLength`] if `id` is empty, and
+    /// [`DecodeError::InvalidEncoding`] if it contains characters outside the ID alphabet,
+    /// or has a length that no byte sequence encodes to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{short_id, DecodeError, ShortId};
+    ///
+    /// let raw = short_id();
+    /// let id: ShortId = raw.parse().unwrap();
+    /// assert_eq!(id.as_str(), raw);
+    ///
+    /// assert_eq!(ShortId::parse("not valid!"), Err(DecodeError::InvalidEncoding));
+    /// ```
+    pub fn parse(id: &str) -> Result<Self, DecodeError> {
+        if id.is_empty() {
+            return Err(DecodeError::InvalidLength(0));
+        }
+        decode(id)?;
+        Ok(ShortId(String::from(id)))
+    }
+}
+
+impl core::str::FromStr for ShortId {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ShortId::parse(s)
+    }
 }
 
 impl core::fmt::Display for ShortId {
@@ -0,0 +1,196 @@
+//! Keyed, reversible obfuscation of integer keys.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use sha2::{Digest, Sha256};
+
+use crate::{encode, DecodeError, ShortId};
+
+/// Number of Feistel rounds applied to each integer.
+const ROUNDS: u8 = 8;
+
+/// Domain separation prefix, so the same key used elsewhere never yields the same
+/// round function.
+const DOMAIN: &[u8] = b"short-id/obfuscate/v1";
+
+/// Keyed, reversible mapping between `u64` values and short IDs.
+///
+/// Sequential database keys leak how many rows exist and make neighbouring records
+/// trivial to guess. `IdObfuscator` hides them without a mapping table: it applies a
+/// keyed permutation (an 8-round Feistel network over the 64-bit value, with SHA-256 as
+/// the round function) and encodes the 8-byte result as an 11-character ID. Every `u64`
+/// maps to a distinct ID, and [`decode()`](Self::decode) recovers the original value with
+/// the same key.
+///
+/// Without the key, consecutive integers produce IDs that look unrelated. Note that the
+/// mapping is a permutation, not authenticated encryption: *any* well-formed 11-character
+/// ID decodes to *some* integer, so look the result up as usual and treat a missing row
+/// as not found. Decoding with a different key yields an unrelated value.
+///
+/// **This type requires the `obfuscate` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::IdObfuscator;
+///
+/// let obfuscator = IdObfuscator::new(b"a secret key from configuration");
+///
+/// let id = obfuscator.encode(42);
+/// assert_eq!(id.len(), 11);
+/// assert_eq!(obfuscator.decode(&id), Ok(42));
+///
+/// // Consecutive keys do not produce similar IDs
+/// assert_ne!(obfuscator.encode(43)[..4], id[..4]);
+///
+/// // A different key does not recover the original value
+/// let other = IdObfuscator::new(b"some other key");
+/// assert_ne!(other.decode(&id), Ok(42));
+/// ```
+#[derive(Clone)]
+pub struct IdObfuscator {
+    /// Hasher state with the domain and key already absorbed.
+    keyed: Sha256,
+}
+
+impl IdObfuscator {
+    /// Creates an obfuscator from a secret key.
+    ///
+    /// The key may have any length; use at least 16 random bytes so the mapping cannot be
+    /// brute-forced. Changing the key changes every ID, so keep it stable for as long as
+    /// its IDs are in circulation.
+    pub fn new(key: &[u8]) -> Self {
+        let mut keyed = Sha256::new();
+        keyed.update(DOMAIN);
+        keyed.update((key.len() as u64).to_be_bytes());
+        keyed.update(key);
+        IdObfuscator { keyed }
+    }
+
+    /// Maps `value` to its 11-character ID.
+    pub fn encode(&self, value: u64) -> String {
+        encode(&self.permute(value).to_be_bytes())
+    }
+
+    /// Maps `value` to its ID as a typed [`ShortId`].
+    pub fn encode_id(&self, value: u64) -> ShortId {
+        ShortId(self.encode(value))
+    }
+
+    /// Recovers the integer an ID was encoded from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it does not decode to exactly 8 bytes.
+    pub fn decode(&self, id: &str) -> Result<u64, DecodeError> {
+        let bytes = crate::decode(id)?;
+        let bytes: [u8; 8] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| DecodeError::InvalidLength(bytes.len()))?;
+        Ok(self.unpermute(u64::from_be_bytes(bytes)))
+    }
+
+    /// Applies the Feistel network.
+    fn permute(&self, value: u64) -> u64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for round in 0..ROUNDS {
+            (left, right) = (right, left ^ self.round(round, right));
+        }
+        (u64::from(left) << 32) | u64::from(right)
+    }
+
+    /// Inverts [`permute()`](Self::permute) by running the rounds backwards.
+    fn unpermute(&self, value: u64) -> u64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for round in (0..ROUNDS).rev() {
+            (left, right) = (right ^ self.round(round, left), left);
+        }
+        (u64::from(left) << 32) | u64::from(right)
+    }
+
+    /// The keyed round function.
+    fn round(&self, round: u8, half: u32) -> u32 {
+        let mut hasher = self.keyed.clone();
+        hasher.update([round]);
+        hasher.update(half.to_be_bytes());
+        let digest = hasher.finalize();
+        u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]])
+    }
+}
+
+impl core::fmt::Debug for IdObfuscator {
+    /// Omits the key material.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IdObfuscator").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{format, vec::Vec};
+
+    #[test]
+    fn test_roundtrip() {
+        let obfuscator = IdObfuscator::new(b"key");
+        for value in [0, 1, 42, u32::MAX as u64, u64::MAX - 1, u64::MAX] {
+            let id = obfuscator.encode(value);
+            assert_eq!(id.len(), 11);
+            assert_eq!(obfuscator.decode(&id), Ok(value));
+        }
+    }
+
+    #[test]
+    fn test_sequential_values_map_to_distinct_ids() {
+        let obfuscator = IdObfuscator::new(b"key");
+        let mut ids: Vec<String> = (0..10_000).map(|value| obfuscator.encode(value)).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 10_000);
+    }
+
+    #[test]
+    fn test_output_is_not_the_plain_encoding() {
+        let obfuscator = IdObfuscator::new(b"key");
+        assert_ne!(obfuscator.encode(42), encode(&42u64.to_be_bytes()));
+    }
+
+    #[test]
+    fn test_keys_produce_different_mappings() {
+        let a = IdObfuscator::new(b"key a");
+        let b = IdObfuscator::new(b"key b");
+        assert_ne!(a.encode(42), b.encode(42));
+        assert_ne!(b.decode(&a.encode(42)), Ok(42));
+        assert_eq!(a.encode(42), IdObfuscator::new(b"key a").encode(42));
+    }
+
+    #[test]
+    fn test_encode_id_matches_encode() {
+        let obfuscator = IdObfuscator::new(b"key");
+        assert_eq!(obfuscator.encode_id(7).as_str(), obfuscator.encode(7));
+    }
+
+    #[test]
+    fn test_decode_rejects_wrong_length() {
+        let obfuscator = IdObfuscator::new(b"key");
+        assert_eq!(
+            obfuscator.decode(&crate::short_id()),
+            Err(DecodeError::InvalidLength(10))
+        );
+        assert_eq!(
+            obfuscator.decode("not valid!"),
+            Err(DecodeError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_debug_hides_key() {
+        let obfuscator = IdObfuscator::new(b"hunter2");
+        assert_eq!(format!("{:?}", obfuscator), "IdObfuscator { .. }");
+    }
+}
@@ -0,0 +1,1696 @@
+//! Timestamp layouts for time-ordered IDs.
+
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rand::{rngs::OsRng, RngCore};
+
+use crate::{decode, encode, BinaryId, Clock, DecodeError, ShortId, SystemClock, MAX_BYTES};
+
+/// Marks the tag byte at the front of IDs not generated with [`OrderedLayout::DEFAULT`].
+const TAGGED: u8 = 0x80;
+
+/// Resolution of the timestamp stored at the front of an ordered ID.
+///
+/// Coarser precision makes the same number of timestamp bytes cover a longer time range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Precision {
+    /// Whole seconds.
+    Seconds,
+    /// Milliseconds.
+    Milliseconds,
+    /// Microseconds (the default).
+    Microseconds,
+}
+
+impl Precision {
+    /// Converts a duration into a tick count at this precision.
+    fn ticks(self, elapsed: Duration) -> u128 {
+        match self {
+            Precision::Seconds => elapsed.as_secs() as u128,
+            Precision::Milliseconds => elapsed.as_millis(),
+            Precision::Microseconds => elapsed.as_micros(),
+        }
+    }
+
+    /// Converts a tick count at this precision back into a duration.
+    fn duration(self, ticks: u64) -> Duration {
+        match self {
+            Precision::Seconds => Duration::from_secs(ticks),
+            Precision::Milliseconds => Duration::from_millis(ticks),
+            Precision::Microseconds => Duration::from_micros(ticks),
+        }
+    }
+}
+
+/// Direction in which ordered IDs sort by creation time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// Oldest first (the default).
+    #[default]
+    Ascending,
+    /// Newest first. The timestamp bits are inverted, so a store that scans keys in
+    /// ascending order returns the most recent IDs first.
+    ///
+    /// Only the bytes are in newest-first order: IDs stored as text compare as strings,
+    /// which does not follow their bytes, so keys need to be stored as [`BinaryId`].
+    Descending,
+}
+
+/// Describes how the timestamp prefix of an ordered ID is laid out.
+///
+/// A layout fixes the [`Precision`] of the timestamp, how many bytes it occupies
+/// (4 to 8, big-endian), the epoch it counts from, and whether IDs sort oldest or newest
+/// first ([`SortOrder`]). Every byte not used by the timestamp is filled with
+/// cryptographically secure random data. Optionally, IDs can be sharded into buckets with
+/// a leading bucket byte (see [`with_buckets()`](Self::with_buckets)).
+///
+/// IDs generated with any layout other than [`DEFAULT`](Self::DEFAULT) start with a tag
+/// byte recording the precision, the number of timestamp bytes, the sort order, and whether
+/// the ID is sharded or counts from a custom epoch. [`timestamp()`](Self::timestamp) and
+/// [`bucket()`](Self::bucket) read the tag, so an ID decodes correctly whichever layout
+/// is used to decode it. Only the value of a custom epoch is not stored: decoding an ID
+/// that counts from one needs a layout with the same epoch. Because every method is
+/// `const`, a layout can be declared once as a `const` and shared by producers and
+/// consumers.
+///
+/// IDs with the default layout have no tag byte, so they are the IDs
+/// [`short_id_ordered()`](crate::short_id_ordered) has always generated. Their first
+/// timestamp byte stays below `0x80` until the year 4253, which tells them apart from
+/// tagged IDs.
+///
+/// Keep the timestamp range in mind when shrinking the layout:
+///
+/// | Bytes | Seconds          | Milliseconds      | Microseconds     |
+/// |-------|------------------|-------------------|------------------|
+/// | 4     | ~136 years       | ~50 days          | ~72 minutes      |
+/// | 5     | ~34,800 years    | ~35 years         | ~13 days         |
+/// | 6     | -                | ~8,900 years      | ~8.9 years       |
+/// | 8     | -                | -                 | ~584,000 years   |
+///
+/// Generating an ID after the range is exhausted (or before the epoch) returns a
+/// [`TimestampOutOfRange`] error rather than silently wrapping around and breaking the sort
+/// order.
+///
+/// # Examples
+///
+/// The default layout is the one used by [`short_id_ordered()`](crate::short_id_ordered):
+///
+/// ```
+/// use short_id::{short_id_ordered, OrderedLayout};
+///
+/// let id = short_id_ordered();
+/// let created = OrderedLayout::DEFAULT.timestamp(&id).unwrap();
+/// assert!(created <= std::time::SystemTime::now());
+/// ```
+///
+/// A compact layout with more room for randomness:
+///
+/// ```
+/// use short_id::{OrderedLayout, Precision};
+/// use std::time::Duration;
+///
+/// const LAYOUT: OrderedLayout = OrderedLayout::new()
+///     .with_precision(Precision::Milliseconds)
+///     .with_timestamp_bytes(6)
+///     .with_epoch(Duration::from_secs(1_577_836_800)); // 2020-01-01
+///
+/// // 1 tag byte + 6 bytes of timestamp + 3 random bytes
+/// let id = LAYOUT.generate(10).unwrap();
+/// assert_eq!(id.len(), 14);
+/// ```
+///
+/// A newest-first layout for stores that scan keys in ascending order:
+///
+/// ```
+/// use short_id::{OrderedLayout, SortOrder};
+///
+/// const NEWEST_FIRST: OrderedLayout = OrderedLayout::new().with_order(SortOrder::Descending);
+///
+/// let older = NEWEST_FIRST.generate(10).unwrap();
+/// std::thread::sleep(std::time::Duration::from_millis(1));
+/// let newer = NEWEST_FIRST.generate(10).unwrap();
+/// assert!(decode_bytes(&newer) < decode_bytes(&older));
+/// # fn decode_bytes(id: &str) -> Vec<u8> {
+/// #     short_id::ShortId::parse(id).unwrap().to_bytes().unwrap()
+/// # }
+/// assert!(NEWEST_FIRST.timestamp(&newer).unwrap() > NEWEST_FIRST.timestamp(&older).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OrderedLayout {
+    precision: Precision,
+    timestamp_bytes: usize,
+    epoch: Duration,
+    order: SortOrder,
+    buckets: u16,
+}
+
+impl OrderedLayout {
+    /// The layout used by [`short_id_ordered()`](crate::short_id_ordered): an 8-byte
+    /// microsecond timestamp counted from the Unix epoch.
+    pub const DEFAULT: OrderedLayout = OrderedLayout {
+        precision: Precision::Microseconds,
+        timestamp_bytes: 8,
+        epoch: Duration::ZERO,
+        order: SortOrder::Ascending,
+        buckets: 1,
+    };
+
+    /// Creates the default layout. Equivalent to [`OrderedLayout::DEFAULT`].
+    pub const fn new() -> Self {
+        Self::DEFAULT
+    }
+
+    /// Sets the precision of the timestamp.
+    pub const fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the number of bytes used by the timestamp.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timestamp_bytes` is less than 4 or greater than 8.
+    pub const fn with_timestamp_bytes(mut self, timestamp_bytes: usize) -> Self {
+        assert!(
+            timestamp_bytes >= 4 && timestamp_bytes <= 8,
+            "timestamp_bytes must be between 4 and 8"
+        );
+        self.timestamp_bytes = timestamp_bytes;
+        self
+    }
+
+    /// Sets the epoch the timestamp counts from, given as an offset from the Unix epoch.
+    pub const fn with_epoch(mut self, since_unix_epoch: Duration) -> Self {
+        self.epoch = since_unix_epoch;
+        self
+    }
+
+    /// Sets whether IDs sort oldest first or newest first.
+    pub const fn with_order(mut self, order: SortOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Shards IDs into `buckets` buckets by prepending a bucket byte.
+    ///
+    /// IDs that start with the timestamp all land at the tail of a range-partitioned store
+    /// (Bigtable, DynamoDB sort keys, Cassandra clustering keys), creating a hotspot. With
+    /// more than one bucket, the tag byte of each ID is followed by a byte between `0` and
+    /// `buckets - 1` derived from its random bytes, spreading writes over `buckets` key ranges. Each
+    /// bucket is still time-ordered; use [`ranges()`](Self::ranges) to scan them all.
+    ///
+    /// The bucket byte counts towards the ID's length. A single bucket (the default)
+    /// means no bucket byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buckets` is 0 or greater than 256.
+    pub const fn with_buckets(mut self, buckets: u16) -> Self {
+        assert!(
+            buckets >= 1 && buckets <= 256,
+            "buckets must be between 1 and 256"
+        );
+        self.buckets = buckets;
+        self
+    }
+
+    /// Returns the precision of the timestamp.
+    pub const fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    /// Returns the number of bytes used by the timestamp.
+    pub const fn timestamp_bytes(&self) -> usize {
+        self.timestamp_bytes
+    }
+
+    /// Returns the epoch the timestamp counts from.
+    pub fn epoch(&self) -> SystemTime {
+        UNIX_EPOCH + self.epoch
+    }
+
+    /// Returns whether IDs sort oldest first or newest first.
+    pub const fn order(&self) -> SortOrder {
+        self.order
+    }
+
+    /// Returns the number of buckets IDs are sharded into.
+    pub const fn buckets(&self) -> u16 {
+        self.buckets
+    }
+
+    /// Generates a time-ordered ID with this layout.
+    ///
+    /// The first [`timestamp_bytes()`](Self::timestamp_bytes) bytes (after the tag byte and,
+    /// if the layout is sharded, the bucket byte) hold the current time, and the remaining
+    /// bytes are random.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TimestampOutOfRange`] if the system clock is before the layout's epoch, or
+    /// if the current time no longer fits in the timestamp bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is smaller than the tag, bucket and timestamp bytes or exceeds 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{OrderedLayout, Precision};
+    ///
+    /// let layout = OrderedLayout::new().with_precision(Precision::Seconds).with_timestamp_bytes(5);
+    /// let id = layout.generate(12).unwrap();
+    /// assert_eq!(id.len(), 16);
+    ///
+    /// // 4 bytes of microseconds since 1970 ran out in 1970
+    /// let exhausted = OrderedLayout::new().with_timestamp_bytes(4);
+    /// assert!(exhausted.generate(10).is_err());
+    /// ```
+    pub fn generate(&self, num_bytes: usize) -> Result<String, TimestampOutOfRange> {
+        self.check_num_bytes(num_bytes);
+
+        let mut bytes = vec![0u8; num_bytes];
+        self.fill(self.now()?, &mut bytes);
+        Ok(encode(&bytes))
+    }
+
+    /// Decodes the creation time of an ordered ID.
+    ///
+    /// The ID is decoded with the layout recorded in its tag byte, so this layout only
+    /// supplies the epoch of IDs that count from a custom one. The result is truncated to
+    /// the ID's [`Precision`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string,
+    /// [`DecodeError::InvalidLength`] if it is too short to hold its timestamp, and
+    /// [`DecodeError::LayoutMismatch`] if its tag is invalid, it counts from a custom epoch
+    /// while this layout counts from the Unix epoch, or its timestamp is too far in the
+    /// future for a [`SystemTime`] (which only happens for IDs not generated with an
+    /// ordered layout).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{DecodeError, OrderedLayout, Precision, ShortId};
+    /// use std::time::Duration;
+    ///
+    /// let id = OrderedLayout::DEFAULT.generate(10).unwrap();
+    /// assert!(OrderedLayout::DEFAULT.timestamp(&id).is_ok());
+    ///
+    /// // The tag byte records the precision, so any layout decodes the ID
+    /// let id = OrderedLayout::new().with_precision(Precision::Seconds).generate(10).unwrap();
+    /// assert!(OrderedLayout::DEFAULT.timestamp(&id).is_ok());
+    ///
+    /// // ... except for the value of a custom epoch
+    /// let layout = OrderedLayout::new().with_epoch(Duration::from_secs(1_577_836_800));
+    /// let id = layout.generate(10).unwrap();
+    /// assert_eq!(OrderedLayout::DEFAULT.timestamp(&id), Err(DecodeError::LayoutMismatch));
+    ///
+    /// // 4 bytes cannot hold an 8-byte timestamp
+    /// let short = ShortId::from_bytes(&[0; 4]);
+    /// assert_eq!(
+    ///     OrderedLayout::DEFAULT.timestamp(short.as_str()),
+    ///     Err(DecodeError::InvalidLength(4))
+    /// );
+    /// ```
+    pub fn timestamp(&self, id: &str) -> Result<SystemTime, DecodeError> {
+        let (layout, bytes) = self.decode_header(id)?;
+        let elapsed = layout.precision.duration(layout.read_timestamp(&bytes));
+        layout
+            .epoch()
+            .checked_add(elapsed)
+            .ok_or(DecodeError::LayoutMismatch)
+    }
+
+    /// Decodes the bucket of an ordered ID.
+    ///
+    /// Always `0` for an ID that is not sharded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`timestamp()`](Self::timestamp).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::OrderedLayout;
+    ///
+    /// let layout = OrderedLayout::new().with_buckets(16);
+    /// let id = layout.generate(10).unwrap();
+    /// assert!(layout.bucket(&id).unwrap() < 16);
+    /// ```
+    pub fn bucket(&self, id: &str) -> Result<u16, DecodeError> {
+        let (layout, bytes) = self.decode_header(id)?;
+        Ok(match layout.bucket_bytes() {
+            0 => 0,
+            _ => bytes[layout.tag_bytes()] as u16,
+        })
+    }
+
+    /// Returns the smallest `num_bytes`-byte ID this layout can produce at `time`.
+    ///
+    /// The timestamp is truncated to the layout's [`Precision`] and clamped to its range.
+    /// Together with [`max_id()`](Self::max_id), this bounds a key range scan over a
+    /// binary column; see [`range()`](Self::range) for the common case.
+    ///
+    /// For a sharded layout, this is the smallest ID in the first bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is smaller than the tag, bucket and timestamp bytes or exceeds 32.
+    pub fn min_id(&self, time: SystemTime, num_bytes: usize) -> BinaryId {
+        self.bound(0, time, num_bytes, 0x00)
+    }
+
+    /// Returns the largest `num_bytes`-byte ID this layout can produce at `time`.
+    ///
+    /// The timestamp is truncated to the layout's [`Precision`] and clamped to its range.
+    ///
+    /// For a sharded layout, this is the largest ID in the last bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is smaller than the tag, bucket and timestamp bytes or exceeds 32.
+    pub fn max_id(&self, time: SystemTime, num_bytes: usize) -> BinaryId {
+        self.bound(self.buckets - 1, time, num_bytes, 0xff)
+    }
+
+    /// Returns the inclusive range of `num_bytes`-byte IDs generated between `start` and
+    /// `end` (inclusive), in byte order.
+    ///
+    /// The bounds are [`BinaryId`]s, which compare by the bytes the IDs decode to - the
+    /// order of an index over a binary column. IDs stored as text compare as strings, which
+    /// does not follow their bytes, so range scans need the IDs stored as [`BinaryId`].
+    ///
+    /// For a [`SortOrder::Descending`] layout, the range starts at the `end` time, since
+    /// newer IDs sort first.
+    ///
+    /// For a sharded layout, the bucket prefix comes before the timestamp, so a single
+    /// range spanning every bucket also contains IDs from outside the time range. Use
+    /// [`ranges()`](Self::ranges) to scan each bucket separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is smaller than the tag, bucket and timestamp bytes or exceeds 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{BinaryId, OrderedLayout, ShortId, SortOrder};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let layout = OrderedLayout::new().with_order(SortOrder::Descending);
+    /// let id = BinaryId::from(ShortId::from(layout.generate(10).unwrap()));
+    ///
+    /// let now = SystemTime::now();
+    /// let last_minute = layout.range(now - Duration::from_secs(60), now, 10);
+    /// assert!(last_minute.contains(&id));
+    /// ```
+    pub fn range(
+        &self,
+        start: SystemTime,
+        end: SystemTime,
+        num_bytes: usize,
+    ) -> RangeInclusive<BinaryId> {
+        let (first, last) = self.oriented(start, end);
+        self.min_id(first, num_bytes)..=self.max_id(last, num_bytes)
+    }
+
+    /// Returns one inclusive range per bucket covering the `num_bytes`-byte IDs generated
+    /// between `start` and `end` (inclusive), in bucket order.
+    ///
+    /// Scanning every returned range (e.g. in parallel) finds exactly the IDs generated
+    /// in the time range. For a layout that is not sharded, this is a single range equal
+    /// to [`range()`](Self::range).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is smaller than the tag, bucket and timestamp bytes or exceeds 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{BinaryId, OrderedLayout, ShortId};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let layout = OrderedLayout::new().with_buckets(4);
+    /// let id = BinaryId::from(ShortId::from(layout.generate(10).unwrap()));
+    ///
+    /// let now = SystemTime::now();
+    /// let scans = layout.ranges(now - Duration::from_secs(60), now, 10);
+    /// assert_eq!(scans.len(), 4);
+    /// assert_eq!(scans.iter().filter(|range| range.contains(&id)).count(), 1);
+    /// ```
+    pub fn ranges(
+        &self,
+        start: SystemTime,
+        end: SystemTime,
+        num_bytes: usize,
+    ) -> Vec<RangeInclusive<BinaryId>> {
+        let (first, last) = self.oriented(start, end);
+        (0..self.buckets)
+            .map(|bucket| {
+                self.bound(bucket, first, num_bytes, 0x00)
+                    ..=self.bound(bucket, last, num_bytes, 0xff)
+            })
+            .collect()
+    }
+
+    /// Orders a time range so that the first time produces the smaller IDs.
+    fn oriented(&self, start: SystemTime, end: SystemTime) -> (SystemTime, SystemTime) {
+        match self.order {
+            SortOrder::Ascending => (start, end),
+            SortOrder::Descending => (end, start),
+        }
+    }
+
+    /// Builds an ID in `bucket` for `time` with every random byte set to `fill`.
+    fn bound(&self, bucket: u16, time: SystemTime, num_bytes: usize, fill: u8) -> BinaryId {
+        self.check_num_bytes(num_bytes);
+
+        let ticks = self.ticks_at(time);
+
+        let mut bytes = vec![fill; num_bytes];
+        if let Some(tag) = self.tag() {
+            bytes[0] = tag;
+        }
+        if self.bucket_bytes() > 0 {
+            bytes[self.tag_bytes()] = bucket as u8;
+        }
+        self.write_timestamp(ticks, &mut bytes);
+        BinaryId::from_bytes(&bytes)
+    }
+
+    /// Asserts that `num_bytes` can hold this layout's tag, bucket and timestamp bytes.
+    pub(crate) fn check_num_bytes(&self, num_bytes: usize) {
+        assert!(
+            num_bytes >= self.header_bytes(),
+            "num_bytes must be at least {} for ordered IDs (got {})",
+            self.header_bytes(),
+            num_bytes
+        );
+        assert!(
+            num_bytes <= MAX_BYTES,
+            "num_bytes must not exceed {} (got {})",
+            MAX_BYTES,
+            num_bytes
+        );
+    }
+
+    /// Decodes an ID and the layout recorded in its tag byte, checking that the ID is long
+    /// enough for that layout's header.
+    fn decode_header(&self, id: &str) -> Result<(OrderedLayout, Vec<u8>), DecodeError> {
+        let bytes = decode(id)?;
+        let layout = match bytes.first() {
+            Some(&tag) => self.tagged(tag)?,
+            None => return Err(DecodeError::InvalidLength(0)),
+        };
+        if bytes.len() < layout.header_bytes() {
+            return Err(DecodeError::InvalidLength(bytes.len()));
+        }
+        Ok((layout, bytes))
+    }
+
+    /// Returns the tag byte written at the front of this layout's IDs, if any.
+    ///
+    /// The tag is `1ffffosc`: a marker bit, the precision and timestamp byte count
+    /// (`precision * 5 + timestamp_bytes - 4`), and one bit each for a descending order,
+    /// sharding and a custom epoch. The default layout writes no tag.
+    fn tag(&self) -> Option<u8> {
+        if *self == Self::DEFAULT {
+            return None;
+        }
+        let precision = match self.precision {
+            Precision::Seconds => 0,
+            Precision::Milliseconds => 1,
+            Precision::Microseconds => 2,
+        };
+        let format = precision * 5 + (self.timestamp_bytes as u8 - 4);
+        let descending = (self.order == SortOrder::Descending) as u8;
+        let sharded = (self.buckets > 1) as u8;
+        let custom_epoch = (self.epoch != Duration::ZERO) as u8;
+        Some(TAGGED | format << 3 | descending << 2 | sharded << 1 | custom_epoch)
+    }
+
+    /// Returns the layout described by the first byte of an ID, taking a custom epoch and
+    /// the bucket count from this layout.
+    fn tagged(&self, tag: u8) -> Result<OrderedLayout, DecodeError> {
+        if tag & TAGGED == 0 {
+            return Ok(Self::DEFAULT);
+        }
+        let format = (tag >> 3) & 0x0f;
+        let precision = match format / 5 {
+            0 => Precision::Seconds,
+            1 => Precision::Milliseconds,
+            2 => Precision::Microseconds,
+            _ => return Err(DecodeError::LayoutMismatch),
+        };
+        let epoch = match tag & 1 {
+            0 => Duration::ZERO,
+            _ if self.epoch == Duration::ZERO => return Err(DecodeError::LayoutMismatch),
+            _ => self.epoch,
+        };
+        let layout = OrderedLayout {
+            precision,
+            timestamp_bytes: (format % 5) as usize + 4,
+            epoch,
+            order: match tag & 4 {
+                0 => SortOrder::Ascending,
+                _ => SortOrder::Descending,
+            },
+            buckets: match tag & 2 {
+                0 => 1,
+                _ => self.buckets.max(2),
+            },
+        };
+        // The default layout is never tagged
+        if layout == Self::DEFAULT {
+            return Err(DecodeError::LayoutMismatch);
+        }
+        Ok(layout)
+    }
+
+    /// Returns the number of tag bytes: 0 for the default layout, 1 otherwise.
+    fn tag_bytes(&self) -> usize {
+        if *self == Self::DEFAULT {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Returns the number of bytes before the timestamp: 1 if sharded, 0 otherwise.
+    fn bucket_bytes(&self) -> usize {
+        if self.buckets > 1 {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Returns the number of bytes before the random data.
+    pub(crate) fn header_bytes(&self) -> usize {
+        self.tag_bytes() + self.bucket_bytes() + self.timestamp_bytes
+    }
+
+    /// Fills `bytes` with the tag, the timestamp `ticks`, random data and the bucket
+    /// derived from the random data.
+    pub(crate) fn fill(&self, ticks: u64, bytes: &mut [u8]) {
+        OsRng.fill_bytes(&mut bytes[self.header_bytes()..]);
+        if let Some(tag) = self.tag() {
+            bytes[0] = tag;
+        }
+        if self.bucket_bytes() > 0 {
+            let random = &bytes[self.header_bytes()..];
+            let seed = if random.is_empty() {
+                OsRng.next_u32()
+            } else {
+                random
+                    .iter()
+                    .take(4)
+                    .fold(0u32, |acc, &byte| (acc << 8) | byte as u32)
+            };
+            bytes[self.tag_bytes()] = (seed % self.buckets as u32) as u8;
+        }
+        self.write_timestamp(ticks, bytes);
+    }
+
+    /// Returns the current time as a tick count at this layout's precision.
+    pub(crate) fn now(&self) -> Result<u64, TimestampOutOfRange> {
+        self.checked_ticks(SystemTime::now())
+    }
+
+    /// Converts `time` into a tick count, failing if it is before the epoch or does not
+    /// fit in the timestamp bytes.
+    pub(crate) fn checked_ticks(&self, time: SystemTime) -> Result<u64, TimestampOutOfRange> {
+        let elapsed = time
+            .duration_since(self.epoch())
+            .map_err(|_| TimestampOutOfRange::new(time, true))?;
+        let ticks = self.precision.ticks(elapsed);
+        if ticks > self.max_ticks() as u128 {
+            return Err(TimestampOutOfRange::new(time, false));
+        }
+        Ok(ticks as u64)
+    }
+
+    /// Converts `time` into a tick count, clamped to the range of the timestamp bytes.
+    pub(crate) fn ticks_at(&self, time: SystemTime) -> u64 {
+        let elapsed = time.duration_since(self.epoch()).unwrap_or_default();
+        self.precision.ticks(elapsed).min(self.max_ticks() as u128) as u64
+    }
+
+    /// Returns the largest tick count the timestamp bytes can hold.
+    pub(crate) fn max_ticks(&self) -> u64 {
+        u64::MAX >> (64 - 8 * self.timestamp_bytes)
+    }
+
+    /// Converts a tick count into the point in time it represents.
+    pub(crate) fn time_of(&self, ticks: u64) -> SystemTime {
+        self.epoch() + self.precision.duration(ticks)
+    }
+
+    /// Writes `ticks` into the timestamp bytes of an ID, after its tag and bucket bytes.
+    pub(crate) fn write_timestamp(&self, ticks: u64, bytes: &mut [u8]) {
+        self.write_ticks(ticks, &mut bytes[self.tag_bytes() + self.bucket_bytes()..]);
+    }
+
+    /// Reads the tick count written by [`write_timestamp()`](Self::write_timestamp).
+    pub(crate) fn read_timestamp(&self, bytes: &[u8]) -> u64 {
+        self.read_ticks(&bytes[self.tag_bytes() + self.bucket_bytes()..])
+    }
+
+    /// Writes `ticks` big-endian into the first timestamp bytes of `bytes`, inverted for
+    /// descending layouts.
+    ///
+    /// Unlike [`write_timestamp()`](Self::write_timestamp), this leaves no room for a tag
+    /// or bucket byte, for formats that embed the timestamp of a fixed layout.
+    pub(crate) fn write_ticks(&self, ticks: u64, bytes: &mut [u8]) {
+        let stored = self.orient(ticks);
+        bytes[..self.timestamp_bytes]
+            .copy_from_slice(&stored.to_be_bytes()[8 - self.timestamp_bytes..]);
+    }
+
+    /// Reads the tick count written by [`write_ticks()`](Self::write_ticks).
+    pub(crate) fn read_ticks(&self, bytes: &[u8]) -> u64 {
+        let mut buf = [0u8; 8];
+        buf[8 - self.timestamp_bytes..].copy_from_slice(&bytes[..self.timestamp_bytes]);
+        self.orient(u64::from_be_bytes(buf))
+    }
+
+    /// Returns whether the timestamp and random bytes `next` come after `last` in this
+    /// layout's sort order.
+    fn follows(&self, next: &[u8], last: &[u8]) -> bool {
+        match self.order {
+            SortOrder::Ascending => next > last,
+            SortOrder::Descending => next < last,
+        }
+    }
+
+    /// Converts between tick counts and stored timestamps (its own inverse).
+    fn orient(&self, ticks: u64) -> u64 {
+        match self.order {
+            SortOrder::Ascending => ticks,
+            SortOrder::Descending => self.max_ticks() - ticks,
+        }
+    }
+}
+
+impl Default for OrderedLayout {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// What a stateful generator does when the system clock moves backwards.
+///
+/// [`OrderedGenerator`] and [`SnowflakeGenerator`](crate::SnowflakeGenerator) remember the
+/// last timestamp they used. If the clock is then stepped back (for example by NTP),
+/// generating with the earlier time would produce IDs that sort before ones already handed
+/// out, so the generator applies one of these policies instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RegressionPolicy {
+    /// Return a [`ClockRegression`] error (the default).
+    #[default]
+    Error,
+    /// Block the calling thread until the clock catches up with the last timestamp.
+    ///
+    /// The generator gives up and returns a [`ClockRegression`] error if the clock is
+    /// still behind after its maximum wait (one second by default).
+    Wait,
+    /// Keep using the last timestamp and increment the rest of the ID, so IDs keep
+    /// increasing while the clock is behind (or decreasing, for a
+    /// [`SortOrder::Descending`] layout).
+    Increment,
+}
+
+/// Stateful generator for time-ordered IDs that handles clock regression.
+///
+/// [`short_id_ordered()`](crate::short_id_ordered) reads the system clock on every call and
+/// trusts it blindly: if the clock is stepped backwards, so are the IDs. An
+/// `OrderedGenerator` remembers the last ID it generated and applies a
+/// [`RegressionPolicy`] whenever the clock is behind it. [`regressions()`](Self::regressions)
+/// reports how often that happened.
+///
+/// Within a bucket of a [sharded](OrderedLayout::with_buckets) layout, or across all IDs
+/// of an unsharded one, IDs from the same generator strictly increase in the layout's
+/// sort order (decrease for a [`SortOrder::Descending`] layout, as bytes): when the
+/// timestamp is no later than the last ID's, the generator steps on from the last ID
+/// instead of using random bytes, and waits for the next tick once those run out.
+///
+/// The generator keeps its state behind a mutex, so a single instance can be shared
+/// between threads (e.g. behind an `Arc`). The time comes from a [`Clock`], the system
+/// clock unless replaced with [`with_clock()`](Self::with_clock).
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{OrderedGenerator, OrderedLayout, RegressionPolicy};
+///
+/// let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 10)
+///     .with_policy(RegressionPolicy::Increment);
+///
+/// let id = generator.generate().unwrap();
+/// assert_eq!(id.as_str().len(), 14);
+/// assert_eq!(generator.regressions(), 0);
+/// ```
+#[derive(Debug)]
+pub struct OrderedGenerator<C = SystemClock> {
+    layout: OrderedLayout,
+    num_bytes: usize,
+    policy: RegressionPolicy,
+    max_wait: Duration,
+    clock: C,
+    last: Mutex<Vec<u8>>,
+    regressions: AtomicU64,
+}
+
+impl OrderedGenerator {
+    /// Creates a generator of `num_bytes`-byte IDs with the given layout.
+    ///
+    /// The generator starts with the [`RegressionPolicy::Error`] policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is smaller than the layout's tag, bucket and timestamp bytes or
+    /// exceeds 32.
+    pub fn new(layout: OrderedLayout, num_bytes: usize) -> Self {
+        layout.check_num_bytes(num_bytes);
+        OrderedGenerator {
+            layout,
+            num_bytes,
+            policy: RegressionPolicy::default(),
+            max_wait: DEFAULT_MAX_WAIT,
+            clock: SystemClock,
+            last: Mutex::new(Vec::new()),
+            regressions: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<C: Clock> OrderedGenerator<C> {
+    /// Replaces the clock that timestamps the generated IDs.
+    ///
+    /// Once the random bytes of a tick run out, the generator waits for this clock to reach
+    /// the next tick, so a clock that never advances blocks it.
+    pub fn with_clock<D: Clock>(self, clock: D) -> OrderedGenerator<D> {
+        OrderedGenerator {
+            layout: self.layout,
+            num_bytes: self.num_bytes,
+            policy: self.policy,
+            max_wait: self.max_wait,
+            clock,
+            last: self.last,
+            regressions: self.regressions,
+        }
+    }
+
+    /// Sets the policy applied when the clock moves backwards.
+    pub fn with_policy(mut self, policy: RegressionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets how long [`RegressionPolicy::Wait`] waits for the clock before returning a
+    /// [`ClockRegression`] error (one second by default).
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Returns the layout of the generated IDs.
+    pub fn layout(&self) -> &OrderedLayout {
+        &self.layout
+    }
+
+    /// Returns the policy applied when the clock moves backwards.
+    pub fn policy(&self) -> RegressionPolicy {
+        self.policy
+    }
+
+    /// Returns how long [`RegressionPolicy::Wait`] waits for the clock.
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// Returns the clock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns how many times [`generate()`](Self::generate) found the clock behind the
+    /// last generated ID, regardless of the policy applied.
+    pub fn regressions(&self) -> u64 {
+        self.regressions.load(Ordering::Relaxed)
+    }
+
+    /// Generates the next ID.
+    ///
+    /// With [`RegressionPolicy::Wait`], the generator does not hold its lock while waiting,
+    /// so other threads calling `generate()` wait for the clock on their own.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GenerateError::ClockRegression`] if the clock is behind the last generated
+    /// ID and the policy is [`RegressionPolicy::Error`] (or [`RegressionPolicy::Wait`] and
+    /// the clock did not catch up within the maximum wait), and
+    /// [`GenerateError::OutOfRange`] if the clock is before the layout's epoch, the
+    /// current time no longer fits in the timestamp bytes, or
+    /// [`RegressionPolicy::Increment`] needs to borrow a tick past the last one.
+    pub fn generate(&self) -> Result<ShortId, GenerateError> {
+        let mut deadline = None;
+        loop {
+            let mut last = self.last.lock().unwrap_or_else(PoisonError::into_inner);
+            let time = self.clock.now();
+            let now = self.layout.checked_ticks(time)?;
+            let mut bytes = vec![0u8; self.num_bytes];
+            self.layout.fill(now, &mut bytes);
+
+            if !last.is_empty() {
+                let last_tick = self.layout.read_timestamp(&last);
+                if now < last_tick {
+                    // Count each call once, not every time a wait wakes up
+                    if deadline.is_none() {
+                        self.regressions.fetch_add(1, Ordering::Relaxed);
+                    }
+                    let regression = ClockRegression::new(
+                        self.layout.time_of(last_tick),
+                        self.layout.time_of(now),
+                    );
+                    match self.policy {
+                        RegressionPolicy::Error => return Err(regression.into()),
+                        RegressionPolicy::Wait => {
+                            drop(last);
+                            let deadline =
+                                *deadline.get_or_insert_with(|| Instant::now() + self.max_wait);
+                            if !pause(&self.clock, regression.last(), Some(deadline)) {
+                                return Err(regression.into());
+                            }
+                            continue;
+                        }
+                        RegressionPolicy::Increment => {}
+                    }
+                }
+
+                // The timestamp alone does not put the ID after the last one: continue
+                // from the last ID, keeping the new bucket
+                let header = self.layout.tag_bytes() + self.layout.bucket_bytes();
+                if !self.layout.follows(&bytes[header..], &last[header..]) {
+                    let random = header + self.layout.timestamp_bytes;
+                    bytes[header..].copy_from_slice(&last[header..]);
+                    if step(&mut bytes[random..], self.layout.order) {
+                        if now >= last_tick {
+                            // The random bytes are used up for this tick: wait for the next
+                            let next = self.layout.time_of(last_tick + 1);
+                            drop(last);
+                            pause(&self.clock, next, None);
+                            continue;
+                        }
+                        // The clock is behind, so waiting would stall: borrow the next tick
+                        if step(&mut bytes[header..random], self.layout.order) {
+                            return Err(TimestampOutOfRange::new(time, false).into());
+                        }
+                    }
+                }
+            }
+
+            *last = bytes;
+            return Ok(ShortId::from_bytes(&last));
+        }
+    }
+}
+
+/// How long [`RegressionPolicy::Wait`] waits for the clock unless configured otherwise.
+pub(crate) const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(1);
+
+/// Sleeps until `clock` reaches `target`, but no later than `deadline`.
+///
+/// Returns `false` without sleeping if the deadline has already passed.
+pub(crate) fn pause(clock: &impl Clock, target: SystemTime, deadline: Option<Instant>) -> bool {
+    let mut remaining = target.duration_since(clock.now()).unwrap_or_default();
+    if let Some(deadline) = deadline {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return false;
+        }
+        remaining = remaining.min(left);
+    }
+    std::thread::sleep(remaining.max(Duration::from_micros(1)));
+    true
+}
+
+/// Moves a big-endian byte string one step in sort order: up by one for ascending
+/// layouts, down by one for descending layouts, wrapping around on overflow.
+///
+/// Returns `true` if the bytes wrapped around (always, for an empty string).
+fn step(bytes: &mut [u8], order: SortOrder) -> bool {
+    for byte in bytes.iter_mut().rev() {
+        let (value, overflow) = match order {
+            SortOrder::Ascending => byte.overflowing_add(1),
+            SortOrder::Descending => byte.overflowing_sub(1),
+        };
+        *byte = value;
+        if !overflow {
+            return false;
+        }
+    }
+    true
+}
+
+/// Error returned when the system clock moves backwards between two generated IDs.
+///
+/// Returned by stateful generators using [`RegressionPolicy::Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockRegression {
+    last: SystemTime,
+    now: SystemTime,
+}
+
+impl ClockRegression {
+    pub(crate) fn new(last: SystemTime, now: SystemTime) -> Self {
+        ClockRegression { last, now }
+    }
+
+    /// Returns the last timestamp the generator used.
+    pub fn last(&self) -> SystemTime {
+        self.last
+    }
+
+    /// Returns the (earlier) time the clock reported when the regression was detected.
+    pub fn now(&self) -> SystemTime {
+        self.now
+    }
+
+    /// Returns how far the clock moved backwards.
+    pub fn drift(&self) -> Duration {
+        self.last.duration_since(self.now).unwrap_or_default()
+    }
+}
+
+impl core::fmt::Display for ClockRegression {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "clock moved backwards by {:?}", self.drift())
+    }
+}
+
+impl std::error::Error for ClockRegression {}
+
+/// Error returned when the clock is outside the range an ordered ID's timestamp can hold.
+///
+/// Returned when generating an ID while the system clock is before the layout's epoch, or
+/// after the timestamp bytes have run out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimestampOutOfRange {
+    time: SystemTime,
+    before_epoch: bool,
+}
+
+impl TimestampOutOfRange {
+    pub(crate) fn new(time: SystemTime, before_epoch: bool) -> Self {
+        TimestampOutOfRange { time, before_epoch }
+    }
+
+    /// Returns the time the clock reported.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Returns `true` if the time is before the epoch, and `false` if it is past the last
+    /// timestamp the layout can hold.
+    pub fn is_before_epoch(&self) -> bool {
+        self.before_epoch
+    }
+}
+
+impl core::fmt::Display for TimestampOutOfRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.before_epoch {
+            write!(f, "system time is before the ID timestamp's epoch")
+        } else {
+            write!(f, "system time is past the range of the ID timestamp")
+        }
+    }
+}
+
+impl std::error::Error for TimestampOutOfRange {}
+
+/// Error returned by the stateful ordered ID generators.
+///
+/// Returned by [`OrderedGenerator::generate()`] and
+/// [`SnowflakeGenerator::generate()`](crate::SnowflakeGenerator::generate).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GenerateError {
+    /// The clock moved backwards and the policy is [`RegressionPolicy::Error`].
+    ClockRegression(ClockRegression),
+    /// The clock is outside the range of the ID timestamp.
+    OutOfRange(TimestampOutOfRange),
+}
+
+impl From<ClockRegression> for GenerateError {
+    fn from(error: ClockRegression) -> Self {
+        GenerateError::ClockRegression(error)
+    }
+}
+
+impl From<TimestampOutOfRange> for GenerateError {
+    fn from(error: TimestampOutOfRange) -> Self {
+        GenerateError::OutOfRange(error)
+    }
+}
+
+impl core::fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GenerateError::ClockRegression(error) => write!(f, "{}", error),
+            GenerateError::OutOfRange(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GenerateError::ClockRegression(error) => Some(error),
+            GenerateError::OutOfRange(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn at_millis(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    /// Returns a clock reading the time set through the returned cell.
+    fn manual_clock(start: SystemTime) -> (Rc<Cell<SystemTime>>, impl Fn() -> SystemTime) {
+        let now = Rc::new(Cell::new(start));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || now.get()
+        };
+        (now, clock)
+    }
+
+    const EPOCH_2020: Duration = Duration::from_secs(1_577_836_800);
+
+    #[test]
+    fn test_default_layout_matches_short_id_ordered() {
+        let id = crate::short_id_ordered();
+        let bytes = decode(&id).unwrap();
+        let ticks = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+        assert_eq!(
+            OrderedLayout::DEFAULT.timestamp(&id).unwrap(),
+            UNIX_EPOCH + Duration::from_micros(ticks)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_roundtrip() {
+        for precision in [
+            Precision::Seconds,
+            Precision::Milliseconds,
+            Precision::Microseconds,
+        ] {
+            let layout = OrderedLayout::new()
+                .with_precision(precision)
+                .with_timestamp_bytes(6)
+                .with_epoch(EPOCH_2020);
+            let before = SystemTime::now();
+            let id = layout.generate(10).unwrap();
+            let after = SystemTime::now();
+
+            let created = layout.timestamp(&id).unwrap();
+            assert!(created <= after);
+            assert!(before.duration_since(created).unwrap_or_default() < Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn test_compact_layout_leaves_more_random_bytes() {
+        let layout = OrderedLayout::new()
+            .with_precision(Precision::Milliseconds)
+            .with_timestamp_bytes(6);
+        let id1 = layout.generate(10).unwrap();
+        let id2 = layout.generate(10).unwrap();
+        assert_eq!(id1.len(), 14);
+        assert_ne!(decode(&id1).unwrap()[6..], decode(&id2).unwrap()[6..]);
+    }
+
+    #[test]
+    fn test_ids_sort_by_time_as_bytes() {
+        let layout = OrderedLayout::new().with_precision(Precision::Milliseconds);
+        let mut ids = Vec::new();
+        for _ in 0..5 {
+            ids.push(decode(&layout.generate(10).unwrap()).unwrap());
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+    }
+
+    #[test]
+    fn test_write_and_read_timestamp() {
+        let layout = OrderedLayout::new().with_timestamp_bytes(5);
+        let mut bytes = [0u8; 7];
+        layout.write_timestamp(0x01_0203_0405, &mut bytes);
+        assert_eq!(bytes, [0, 1, 2, 3, 4, 5, 0]);
+        assert_eq!(layout.read_timestamp(&bytes), 0x01_0203_0405);
+        assert_eq!(layout.read_ticks(&bytes[1..]), 0x01_0203_0405);
+        assert_eq!(layout.max_ticks(), 0xff_ffff_ffff);
+    }
+
+    #[test]
+    fn test_timestamp_rejects_short_ids() {
+        let layout = OrderedLayout::new().with_timestamp_bytes(6);
+        let id = encode(&[layout.tag().unwrap(), 0, 0, 0, 0, 0]);
+        assert_eq!(layout.timestamp(&id), Err(DecodeError::InvalidLength(6)));
+        assert_eq!(layout.timestamp(""), Err(DecodeError::InvalidLength(0)));
+        assert_eq!(layout.timestamp("!!"), Err(DecodeError::InvalidEncoding));
+    }
+
+    #[test]
+    fn test_tag_records_layout() {
+        for precision in [
+            Precision::Seconds,
+            Precision::Milliseconds,
+            Precision::Microseconds,
+        ] {
+            for timestamp_bytes in 4..=8 {
+                for order in [SortOrder::Ascending, SortOrder::Descending] {
+                    for buckets in [1, 16] {
+                        let layout = OrderedLayout::new()
+                            .with_precision(precision)
+                            .with_timestamp_bytes(timestamp_bytes)
+                            .with_epoch(EPOCH_2020)
+                            .with_order(order)
+                            .with_buckets(buckets);
+                        let tag = layout.tag().unwrap();
+                        assert_eq!(tag & TAGGED, TAGGED);
+                        assert_eq!(layout.tagged(tag), Ok(layout));
+                    }
+                }
+            }
+        }
+        assert_eq!(OrderedLayout::DEFAULT.tag(), None);
+        assert_eq!(OrderedLayout::DEFAULT.header_bytes(), 8);
+    }
+
+    #[test]
+    fn test_any_layout_decodes_tagged_ids() {
+        let seconds = OrderedLayout::new()
+            .with_precision(Precision::Seconds)
+            .with_timestamp_bytes(5)
+            .with_buckets(4);
+        let id = seconds.generate(10).unwrap();
+        assert_eq!(
+            OrderedLayout::DEFAULT.timestamp(&id),
+            seconds.timestamp(&id)
+        );
+        assert_eq!(OrderedLayout::DEFAULT.bucket(&id), seconds.bucket(&id));
+
+        let default = crate::short_id_ordered();
+        assert_eq!(
+            seconds.timestamp(&default),
+            OrderedLayout::DEFAULT.timestamp(&default)
+        );
+    }
+
+    #[test]
+    fn test_custom_epoch_must_be_supplied() {
+        let layout = OrderedLayout::new().with_epoch(EPOCH_2020);
+        let id = layout.generate(10).unwrap();
+        assert_eq!(
+            OrderedLayout::DEFAULT.timestamp(&id),
+            Err(DecodeError::LayoutMismatch)
+        );
+        let other = OrderedLayout::new()
+            .with_precision(Precision::Seconds)
+            .with_epoch(EPOCH_2020);
+        assert_eq!(other.timestamp(&id), layout.timestamp(&id));
+    }
+
+    #[test]
+    fn test_unrepresentable_timestamp_is_rejected() {
+        let layout = OrderedLayout::new()
+            .with_precision(Precision::Seconds)
+            .with_timestamp_bytes(8);
+        let mut bytes = [0xff; 10];
+        bytes[0] = layout.tag().unwrap();
+        assert_eq!(
+            layout.timestamp(&encode(&bytes)),
+            Err(DecodeError::LayoutMismatch)
+        );
+    }
+
+    #[test]
+    fn test_invalid_tags_are_rejected() {
+        // Format 15 does not exist, and the default layout is never tagged
+        let descending = OrderedLayout::new().with_order(SortOrder::Descending);
+        for tag in [0xf8, descending.tag().unwrap() & !4] {
+            let id = encode(&[tag; 10]);
+            assert_eq!(
+                OrderedLayout::DEFAULT.timestamp(&id),
+                Err(DecodeError::LayoutMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn test_generator_error_policy() {
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 10).with_clock(clock);
+        let first = generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_000));
+        let Err(GenerateError::ClockRegression(err)) = generator.generate() else {
+            panic!("expected a clock regression");
+        };
+        assert_eq!(err.last(), at_millis(1_700_000_000_000));
+        assert_eq!(err.now(), at_millis(1_699_999_999_000));
+        assert_eq!(err.drift(), Duration::from_secs(1));
+        assert_eq!(generator.regressions(), 1);
+
+        // The failed call does not move the generator
+        now.set(at_millis(1_700_000_000_000));
+        let next = generator.generate().unwrap();
+        assert!(BinaryId::from(first) < BinaryId::from(next));
+    }
+
+    #[test]
+    fn test_generator_increment_policy() {
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 10)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Increment);
+        let mut ids = vec![generator.generate().unwrap()];
+
+        now.set(at_millis(1_699_999_999_000));
+        ids.push(generator.generate().unwrap());
+        ids.push(generator.generate().unwrap());
+        // Back at the last timestamp, but the suffix must still continue from the last ID
+        now.set(at_millis(1_700_000_000_000));
+        ids.push(generator.generate().unwrap());
+        now.set(at_millis(1_700_000_000_001));
+        ids.push(generator.generate().unwrap());
+
+        let binary: Vec<BinaryId> = ids.iter().cloned().map(BinaryId::from).collect();
+        assert!(binary.windows(2).all(|pair| pair[0] < pair[1]));
+        for id in &ids[..4] {
+            assert_eq!(
+                generator.layout.timestamp(id.as_str()),
+                Ok(at_millis(1_700_000_000_000))
+            );
+        }
+        assert_eq!(
+            generator.layout.timestamp(ids[4].as_str()),
+            Ok(at_millis(1_700_000_000_001))
+        );
+        assert_eq!(generator.regressions(), 2);
+    }
+
+    #[test]
+    fn test_generator_increment_policy_descending() {
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator =
+            OrderedGenerator::new(OrderedLayout::new().with_order(SortOrder::Descending), 10)
+                .with_clock(clock)
+                .with_policy(RegressionPolicy::Increment);
+        let mut ids = vec![generator.generate().unwrap()];
+
+        now.set(at_millis(1_699_999_999_000));
+        ids.push(generator.generate().unwrap());
+        now.set(at_millis(1_700_000_000_000));
+        ids.push(generator.generate().unwrap());
+
+        let binary: Vec<BinaryId> = ids.iter().cloned().map(BinaryId::from).collect();
+        assert!(binary.windows(2).all(|pair| pair[0] > pair[1]));
+        for id in &ids {
+            assert_eq!(
+                generator.layout.timestamp(id.as_str()),
+                Ok(at_millis(1_700_000_000_000))
+            );
+        }
+        assert_eq!(generator.regressions(), 1);
+    }
+
+    #[test]
+    fn test_generator_wait_policy() {
+        // Each reading advances the clock by a millisecond
+        let now = Rc::new(Cell::new(at_millis(1_700_000_000_000)));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || {
+                let time = now.get();
+                now.set(time + Duration::from_millis(1));
+                time
+            }
+        };
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 10)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Wait);
+        let first = generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_995));
+        let second = generator.generate().unwrap();
+        assert!(BinaryId::from(first) < BinaryId::from(second.clone()));
+        assert!(
+            generator.layout.timestamp(second.as_str()).unwrap() >= at_millis(1_700_000_000_000)
+        );
+        assert_eq!(generator.regressions(), 1);
+    }
+
+    #[test]
+    fn test_generator_wait_policy_gives_up() {
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 10)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Wait)
+            .with_max_wait(Duration::from_millis(20));
+        generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_000));
+        let started = Instant::now();
+        let Err(GenerateError::ClockRegression(err)) = generator.generate() else {
+            panic!("expected a clock regression");
+        };
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(err.drift(), Duration::from_secs(1));
+        assert_eq!(generator.regressions(), 1);
+    }
+
+    #[test]
+    fn test_generator_waits_for_next_tick_without_random_bytes() {
+        // Each reading advances the clock by a microsecond, one tick of the layout
+        let now = Rc::new(Cell::new(at_millis(1_700_000_000_000)));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || {
+                let time = now.get();
+                now.set(time + Duration::from_micros(1));
+                time
+            }
+        };
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 8).with_clock(clock);
+        let times: Vec<SystemTime> = (0..20)
+            .map(|_| {
+                let id = generator.generate().unwrap();
+                generator.layout.timestamp(id.as_str()).unwrap()
+            })
+            .collect();
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(generator.regressions(), 0);
+    }
+
+    #[test]
+    fn test_generator_increment_borrows_next_tick_without_random_bytes() {
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 8)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Increment);
+        generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_000));
+        let id = generator.generate().unwrap();
+        assert_eq!(
+            generator.layout.timestamp(id.as_str()),
+            Ok(at_millis(1_700_000_000_000) + Duration::from_micros(1))
+        );
+    }
+
+    #[test]
+    fn test_generator_increment_stops_at_last_tick() {
+        let layout = OrderedLayout::new()
+            .with_precision(Precision::Seconds)
+            .with_timestamp_bytes(4);
+        let last = UNIX_EPOCH + Duration::from_secs(layout.max_ticks());
+        let (now, clock) = manual_clock(last);
+        let generator = OrderedGenerator::new(layout, 5)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Increment);
+        generator.generate().unwrap();
+
+        now.set(last - Duration::from_secs(1));
+        let Err(GenerateError::OutOfRange(err)) = generator.generate() else {
+            panic!("expected the timestamp to be out of range");
+        };
+        assert!(!err.is_before_epoch());
+        assert_eq!(err.time(), last - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_generator_is_monotonic_within_a_tick() {
+        let (_, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 16).with_clock(clock);
+        let ids: Vec<BinaryId> = (0..100)
+            .map(|_| BinaryId::from(generator.generate().unwrap()))
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(generator.regressions(), 0);
+    }
+
+    #[test]
+    fn test_generator_without_regression() {
+        let generator = OrderedGenerator::new(OrderedLayout::DEFAULT, 12);
+        let ids: Vec<ShortId> = (0..100).map(|_| generator.generate().unwrap()).collect();
+        assert!(ids.iter().all(|id| id.as_str().len() == 16));
+        assert_eq!(generator.regressions(), 0);
+    }
+
+    #[test]
+    fn test_step_carries() {
+        let mut bytes = [0x00, 0xff, 0xff];
+        assert!(!step(&mut bytes, SortOrder::Ascending));
+        assert_eq!(bytes, [0x01, 0x00, 0x00]);
+        assert!(!step(&mut bytes, SortOrder::Descending));
+        assert_eq!(bytes, [0x00, 0xff, 0xff]);
+        assert!(step(&mut bytes[1..], SortOrder::Ascending));
+        assert_eq!(bytes, [0x00, 0x00, 0x00]);
+        assert!(step(&mut [], SortOrder::Ascending));
+    }
+
+    #[test]
+    fn test_descending_ids_sort_newest_first() {
+        let layout = OrderedLayout::new()
+            .with_precision(Precision::Milliseconds)
+            .with_order(SortOrder::Descending);
+        let mut ids = Vec::new();
+        for _ in 0..5 {
+            ids.push(layout.generate(10).unwrap());
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        let mut sorted: Vec<Vec<u8>> = ids.iter().map(|id| decode(id).unwrap()).collect();
+        sorted.sort();
+        sorted.reverse();
+        assert_eq!(
+            sorted.iter().map(|bytes| encode(bytes)).collect::<Vec<_>>(),
+            ids
+        );
+
+        let times: Vec<SystemTime> = ids.iter().map(|id| layout.timestamp(id).unwrap()).collect();
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_descending_timestamp_roundtrip() {
+        let layout = OrderedLayout::new()
+            .with_timestamp_bytes(6)
+            .with_precision(Precision::Milliseconds)
+            .with_order(SortOrder::Descending);
+        let mut bytes = [0u8; 6];
+        layout.write_ticks(1, &mut bytes);
+        assert_eq!(bytes, [0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+        assert_eq!(layout.read_ticks(&bytes), 1);
+    }
+
+    #[test]
+    fn test_bounds_contain_generated_ids() {
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            let layout = OrderedLayout::new()
+                .with_precision(Precision::Seconds)
+                .with_timestamp_bytes(5)
+                .with_order(order);
+            let id = layout.generate(10).unwrap();
+            let created = layout.timestamp(&id).unwrap();
+            let id = BinaryId::from(ShortId::from(id));
+
+            assert!(layout.min_id(created, 10) <= id);
+            assert!(layout.max_id(created, 10) >= id);
+
+            let range = layout.range(created, created + Duration::from_secs(5), 10);
+            assert!(range.contains(&id));
+            let later = layout.range(
+                created + Duration::from_secs(1),
+                created + Duration::from_secs(5),
+                10,
+            );
+            assert!(!later.contains(&id));
+        }
+    }
+
+    #[test]
+    fn test_bounds_clamp_out_of_range_times() {
+        let layout = OrderedLayout::new()
+            .with_timestamp_bytes(4)
+            .with_epoch(EPOCH_2020);
+        let tag = layout.tag().unwrap();
+        assert_eq!(
+            layout.min_id(UNIX_EPOCH, 5),
+            BinaryId::from_bytes(&[tag, 0x00, 0x00, 0x00, 0x00])
+        );
+        assert_eq!(
+            layout.max_id(SystemTime::now(), 5),
+            BinaryId::from_bytes(&[tag, 0xff, 0xff, 0xff, 0xff])
+        );
+    }
+
+    #[test]
+    fn test_sharded_ids_spread_over_buckets() {
+        let layout = OrderedLayout::new()
+            .with_precision(Precision::Milliseconds)
+            .with_timestamp_bytes(6)
+            .with_buckets(4);
+        let mut seen = [0usize; 4];
+        for _ in 0..400 {
+            let id = layout.generate(10).unwrap();
+            let bytes = decode(&id).unwrap();
+            let bucket = layout.bucket(&id).unwrap();
+            assert_eq!(bucket as u8, bytes[1]);
+            let seed = bytes[8..]
+                .iter()
+                .fold(0u32, |acc, &byte| (acc << 8) | byte as u32);
+            assert_eq!(seed % 4, bucket as u32);
+            seen[bucket as usize] += 1;
+        }
+        assert!(seen.iter().all(|&count| count > 0), "{:?}", seen);
+    }
+
+    #[test]
+    fn test_sharded_timestamp_follows_bucket_byte() {
+        let layout = OrderedLayout::new()
+            .with_precision(Precision::Milliseconds)
+            .with_timestamp_bytes(6)
+            .with_buckets(256);
+        assert_eq!(layout.header_bytes(), 8);
+
+        let mut bytes = [0u8; 9];
+        bytes[8] = 0xab;
+        layout.fill(0x0102_0304_0506, &mut bytes);
+        assert_eq!(bytes[0], layout.tag().unwrap());
+        assert_eq!(bytes[2..8], [1, 2, 3, 4, 5, 6]);
+        assert_eq!(layout.read_timestamp(&bytes), 0x0102_0304_0506);
+
+        let created = layout.timestamp(&layout.generate(8).unwrap()).unwrap();
+        assert!(SystemTime::now().duration_since(created).unwrap() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_ranges_cover_each_bucket_once() {
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            let layout = OrderedLayout::new()
+                .with_precision(Precision::Seconds)
+                .with_timestamp_bytes(5)
+                .with_order(order)
+                .with_buckets(8);
+            let ids: Vec<BinaryId> = (0..50)
+                .map(|_| BinaryId::from(ShortId::from(layout.generate(10).unwrap())))
+                .collect();
+            let created = layout.timestamp(ids[0].0.as_str()).unwrap();
+            let scans = layout.ranges(created, created + Duration::from_secs(1), 10);
+            assert_eq!(scans.len(), 8);
+
+            for id in &ids {
+                let matching: Vec<usize> = (0..scans.len())
+                    .filter(|&bucket| scans[bucket].contains(id))
+                    .collect();
+                assert_eq!(matching, [layout.bucket(id.0.as_str()).unwrap() as usize]);
+            }
+
+            let later = layout.ranges(
+                created + Duration::from_secs(5),
+                created + Duration::from_secs(10),
+                10,
+            );
+            assert!(ids
+                .iter()
+                .all(|id| later.iter().all(|range| !range.contains(id))));
+        }
+    }
+
+    #[test]
+    fn test_unsharded_ranges_match_range() {
+        let layout = OrderedLayout::DEFAULT;
+        let now = SystemTime::now();
+        let start = now - Duration::from_secs(60);
+        assert_eq!(
+            layout.ranges(start, now, 10),
+            [layout.range(start, now, 10)]
+        );
+        assert_eq!(layout.bucket(&layout.generate(10).unwrap()), Ok(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bytes must be at least 10 for ordered IDs")]
+    fn test_sharded_generate_needs_bucket_byte() {
+        OrderedLayout::new().with_buckets(2).generate(9).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "buckets must be between 1 and 256")]
+    fn test_too_many_buckets_panics() {
+        OrderedLayout::new().with_buckets(257);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bytes must be at least 7 for ordered IDs")]
+    fn test_generate_smaller_than_timestamp_panics() {
+        OrderedLayout::new()
+            .with_timestamp_bytes(6)
+            .generate(6)
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "timestamp_bytes must be between 4 and 8")]
+    fn test_timestamp_bytes_out_of_range_panics() {
+        OrderedLayout::new().with_timestamp_bytes(3);
+    }
+
+    #[test]
+    fn test_timestamp_overflow_is_an_error() {
+        let err = OrderedLayout::new()
+            .with_timestamp_bytes(4)
+            .generate(10)
+            .unwrap_err();
+        assert!(!err.is_before_epoch());
+        assert_eq!(
+            err.to_string(),
+            "system time is past the range of the ID timestamp"
+        );
+    }
+
+    #[test]
+    fn test_epoch_in_future_is_an_error() {
+        let layout = OrderedLayout::new().with_epoch(Duration::from_secs(u32::MAX as u64 * 4));
+        let err = layout.generate(10).unwrap_err();
+        assert!(err.is_before_epoch());
+        assert!(err.time() < layout.epoch());
+
+        let generator = OrderedGenerator::new(layout, 10);
+        assert!(matches!(
+            generator.generate(),
+            Err(GenerateError::OutOfRange(err)) if err.is_before_epoch()
+        ));
+    }
+}
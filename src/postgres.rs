@@ -0,0 +1,136 @@
+//! `postgres-types` support for [`ShortId`], as used by `tokio-postgres` and `postgres`.
+//!
+//! Postgres tells the client the type of every parameter and column, so one impl covers
+//! both storage forms: `text` and `varchar` hold the ID string, and `bytea` holds the
+//! bytes it decodes to. No [`BinaryId`](crate::BinaryId) wrapper is needed.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+use postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+use crate::ShortId;
+
+/// Returns `true` for the column types an ID can be stored in.
+fn accepts(ty: &Type) -> bool {
+    matches!(*ty, Type::TEXT | Type::VARCHAR | Type::BYTEA)
+}
+
+impl ToSql for ShortId {
+    /// Writes the ID string for `text` and `varchar`, and its bytes for `bytea`.
+    ///
+    /// Writing to `bytea` fails with a [`DecodeError`](crate::DecodeError) if the ID is not
+    /// a valid encoding.
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        if *ty == Type::BYTEA {
+            out.put_slice(&self.to_bytes()?);
+        } else {
+            out.put_slice(self.as_str().as_bytes());
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for ShortId {
+    /// Reads the ID string from `text` and `varchar`, and its bytes from `bytea`.
+    ///
+    /// Strings that are not a valid ID encoding are rejected with a
+    /// [`DecodeError`](crate::DecodeError).
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if *ty == Type::BYTEA {
+            return Ok(ShortId::from_bytes(raw));
+        }
+        Ok(ShortId::parse(core::str::from_utf8(raw)?)?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        accepts(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::DecodeError;
+
+    fn to_sql(id: &ShortId, ty: &Type) -> Result<BytesMut, Box<dyn Error + Sync + Send>> {
+        let mut out = BytesMut::new();
+        id.to_sql_checked(ty, &mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_accepts() {
+        for ty in [Type::TEXT, Type::VARCHAR, Type::BYTEA] {
+            assert!(
+                <ShortId as ToSql>::accepts(&ty),
+                "{} should be accepted",
+                ty
+            );
+            assert!(
+                <ShortId as FromSql>::accepts(&ty),
+                "{} should be accepted",
+                ty
+            );
+        }
+        for ty in [Type::INT8, Type::UUID, Type::JSON] {
+            assert!(
+                !<ShortId as ToSql>::accepts(&ty),
+                "{} should be rejected",
+                ty
+            );
+            assert!(
+                !<ShortId as FromSql>::accepts(&ty),
+                "{} should be rejected",
+                ty
+            );
+        }
+    }
+
+    #[test]
+    fn test_text_roundtrip() {
+        let id = ShortId::random();
+        for ty in [Type::TEXT, Type::VARCHAR] {
+            let raw = to_sql(&id, &ty).unwrap();
+            assert_eq!(&raw[..], id.as_str().as_bytes());
+            assert_eq!(ShortId::from_sql(&ty, &raw).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn test_bytea_roundtrip() {
+        let id = ShortId::random();
+        let raw = to_sql(&id, &Type::BYTEA).unwrap();
+        assert_eq!(raw.to_vec(), id.to_bytes().unwrap());
+        assert_eq!(ShortId::from_sql(&Type::BYTEA, &raw).unwrap(), id);
+    }
+
+    #[test]
+    fn test_rejects_wrong_types() {
+        assert!(to_sql(&ShortId::random(), &Type::INT8).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_values() {
+        let err = ShortId::from_sql(&Type::TEXT, b"not valid!").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<DecodeError>(),
+            Some(&DecodeError::InvalidEncoding)
+        );
+        assert!(ShortId::from_sql(&Type::TEXT, &[0xff, 0xfe]).is_err());
+
+        let invalid = ShortId::from(String::from("not valid!"));
+        assert!(to_sql(&invalid, &Type::BYTEA).is_err());
+    }
+}
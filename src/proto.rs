@@ -0,0 +1,162 @@
+//! Protocol Buffers messages for exchanging IDs, for use with `prost` and `tonic`.
+//!
+//! The message definitions are in `proto/short_id/v1/short_id.proto` in the crate source.
+//! Import that file into your own `.proto` files to use the message in fields:
+//!
+//! ```proto
+//! import "short_id/v1/short_id.proto";
+//!
+//! message GetOrderRequest {
+//!   short_id.v1.ShortId order_id = 1;
+//! }
+//! ```
+//!
+//! and map the package to this module instead of generating it again, e.g. with
+//! `prost_build::Config::extern_path(".short_id.v1", "::short_id::proto::v1")`.
+//!
+//! **This module requires the `prost` feature.**
+
+/// Version 1 of the `short_id` package.
+pub mod v1 {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use crate::{DecodeError, MAX_BYTES};
+
+    /// The `short_id.v1.ShortId` message: an ID in its compact binary form.
+    ///
+    /// The message carries the bytes the ID decodes to (10 bytes for a default ID) rather
+    /// than its 14-character string. Convert with `TryFrom` in both directions; decoding a
+    /// message rejects an empty value, which is what a missing field decodes to in proto3,
+    /// and values longer than the 32 bytes an ID can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use prost::Message;
+    /// use short_id::{proto, ShortId};
+    ///
+    /// let id = ShortId::random();
+    /// let message = proto::v1::ShortId::try_from(&id).unwrap();
+    /// let wire = message.encode_to_vec();
+    /// assert_eq!(wire.len(), 12);
+    ///
+    /// let received = proto::v1::ShortId::decode(wire.as_slice()).unwrap();
+    /// assert_eq!(ShortId::try_from(received), Ok(id));
+    /// ```
+    #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+    pub struct ShortId {
+        /// The bytes the ID decodes to.
+        #[prost(bytes = "vec", tag = "1")]
+        pub value: Vec<u8>,
+    }
+
+    impl TryFrom<&crate::ShortId> for ShortId {
+        type Error = DecodeError;
+
+        /// Converts an ID to its message.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`DecodeError::InvalidEncoding`] if the ID is not a valid encoding.
+        fn try_from(id: &crate::ShortId) -> Result<Self, Self::Error> {
+            Ok(ShortId {
+                value: id.to_bytes()?,
+            })
+        }
+    }
+
+    impl TryFrom<crate::ShortId> for ShortId {
+        type Error = DecodeError;
+
+        fn try_from(id: crate::ShortId) -> Result<Self, Self::Error> {
+            ShortId::try_from(&id)
+        }
+    }
+
+    impl TryFrom<ShortId> for crate::ShortId {
+        type Error = DecodeError;
+
+        /// Converts a received message to an ID.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`DecodeError::InvalidLength`] if the value is empty, e.g. because the
+        /// sender left the field unset, or longer than 32 bytes.
+        fn try_from(message: ShortId) -> Result<Self, Self::Error> {
+            let len = message.value.len();
+            if len == 0 || len > MAX_BYTES {
+                return Err(DecodeError::InvalidLength(len));
+            }
+            Ok(crate::ShortId::from_bytes(&message.value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::String, vec};
+
+    use prost::Message;
+
+    use crate::{DecodeError, ShortId};
+
+    #[test]
+    fn test_wire_format() {
+        let id = ShortId::from_bytes(&[1, 2, 3]);
+        let wire = v1::ShortId::try_from(&id).unwrap().encode_to_vec();
+        // Field 1, length-delimited, 3 bytes
+        assert_eq!(wire, vec![0x0a, 3, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let id = ShortId::random();
+        let wire = v1::ShortId::try_from(id.clone()).unwrap().encode_to_vec();
+        let decoded = v1::ShortId::decode(wire.as_slice()).unwrap();
+        assert_eq!(ShortId::try_from(decoded), Ok(id));
+    }
+
+    #[test]
+    fn test_rejects_missing_value() {
+        let decoded = v1::ShortId::decode(&[][..]).unwrap();
+        assert_eq!(
+            ShortId::try_from(decoded),
+            Err(DecodeError::InvalidLength(0))
+        );
+    }
+
+    #[test]
+    fn test_rejects_oversized_value() {
+        let message = v1::ShortId { value: vec![7; 33] };
+        assert_eq!(
+            ShortId::try_from(message),
+            Err(DecodeError::InvalidLength(33))
+        );
+
+        let message = v1::ShortId { value: vec![7; 32] };
+        assert_eq!(
+            ShortId::try_from(message).unwrap().to_bytes(),
+            Ok(vec![7; 32])
+        );
+    }
+
+    #[test]
+    fn test_invalid_id_fails_to_convert() {
+        let id = ShortId::from(String::from("not valid!"));
+        assert_eq!(
+            v1::ShortId::try_from(&id),
+            Err(DecodeError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn test_proto_file_matches() {
+        let proto = include_str!("../proto/short_id/v1/short_id.proto");
+        assert!(proto.contains("package short_id.v1;"));
+        assert!(proto.contains("message ShortId {\n  bytes value = 1;\n}"));
+    }
+}
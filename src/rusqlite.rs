@@ -0,0 +1,173 @@
+//! `rusqlite` support for [`ShortId`] and [`BinaryId`].
+//!
+//! SQLite compares `BLOB` values with `memcmp`, so an index over a [`BinaryId`] column
+//! keeps ordered IDs of the same length in creation order. `TEXT` columns compare the
+//! base64url characters, which are not in the order of the bytes they encode.
+
+use ::rusqlite::{
+    types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef},
+    Error, Result,
+};
+
+use crate::{BinaryId, ShortId};
+
+impl ToSql for ShortId {
+    /// Stores the ID as `TEXT`.
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for ShortId {
+    /// Reads an ID stored as `TEXT` or as a `BLOB` of its bytes.
+    ///
+    /// Text that is not a valid ID encoding is rejected with [`FromSqlError::Other`]
+    /// wrapping a [`DecodeError`](crate::DecodeError), and other column types with
+    /// [`FromSqlError::InvalidType`].
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Text(_) => {
+                ShortId::parse(value.as_str()?).map_err(|e| FromSqlError::Other(e.into()))
+            }
+            ValueRef::Blob(bytes) => Ok(ShortId::from_bytes(bytes)),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl ToSql for BinaryId {
+    /// Stores the ID as a `BLOB` of its bytes.
+    ///
+    /// Fails with [`Error::ToSqlConversionFailure`] if the wrapped ID is not a valid
+    /// encoding.
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        let bytes = self
+            .to_bytes()
+            .map_err(|e| Error::ToSqlConversionFailure(e.into()))?;
+        Ok(ToSqlOutput::Owned(Value::Blob(bytes)))
+    }
+}
+
+impl FromSql for BinaryId {
+    /// Reads an ID stored as a `BLOB`, rejecting other column types with
+    /// [`FromSqlError::InvalidType`].
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Blob(bytes) => Ok(BinaryId::from_bytes(bytes)),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ::rusqlite::Connection;
+
+    use crate::DecodeError;
+
+    fn connect() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE ids (text_id TEXT, blob_id BLOB);
+             CREATE INDEX ids_text ON ids (text_id);
+             CREATE INDEX ids_blob ON ids (blob_id);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_text_and_blob_roundtrip() {
+        let conn = connect();
+        let id = ShortId::random();
+        conn.execute(
+            "INSERT INTO ids VALUES (?1, ?2)",
+            (&id, BinaryId(id.clone())),
+        )
+        .unwrap();
+
+        let (text_id, blob_id, kinds): (ShortId, BinaryId, String) = conn
+            .query_row(
+                "SELECT text_id, blob_id, typeof(text_id) || typeof(blob_id) FROM ids",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(text_id, id);
+        assert_eq!(blob_id.into_inner(), id);
+        assert_eq!(kinds, "textblob");
+
+        // A plain ShortId reads the blob form too
+        let from_blob: ShortId = conn
+            .query_row("SELECT blob_id FROM ids", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(from_blob, id);
+    }
+
+    #[test]
+    fn test_rejects_malformed_values() {
+        let conn = connect();
+        conn.execute("INSERT INTO ids VALUES ('not valid!', 42)", [])
+            .unwrap();
+
+        let err = conn
+            .query_row("SELECT text_id FROM ids", [], |row| {
+                row.get::<_, ShortId>(0)
+            })
+            .unwrap_err();
+        match err {
+            Error::FromSqlConversionFailure(_, _, source) => {
+                assert_eq!(
+                    source.downcast_ref::<DecodeError>(),
+                    Some(&DecodeError::InvalidEncoding)
+                );
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let err = conn
+            .query_row("SELECT blob_id FROM ids", [], |row| {
+                row.get::<_, BinaryId>(0)
+            })
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidColumnType(..)));
+    }
+
+    #[test]
+    fn test_invalid_binary_id_fails_to_bind() {
+        let conn = connect();
+        let id = BinaryId(ShortId::from(String::from("not valid!")));
+        let err = conn
+            .execute("INSERT INTO ids (blob_id) VALUES (?1)", [id])
+            .unwrap_err();
+        assert!(matches!(err, Error::ToSqlConversionFailure(_)));
+    }
+
+    #[test]
+    fn test_blob_index_keeps_ordered_ids_in_order() {
+        let conn = connect();
+        let mut ids = Vec::new();
+        for _ in 0..20 {
+            let id = ShortId::ordered();
+            conn.execute(
+                "INSERT INTO ids VALUES (?1, ?2)",
+                (&id, BinaryId(id.clone())),
+            )
+            .unwrap();
+            ids.push(id);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT blob_id FROM ids ORDER BY blob_id")
+            .unwrap();
+        let stored: Vec<ShortId> = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(stored, ids, "blob_id should sort in creation order");
+    }
+}
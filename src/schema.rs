@@ -0,0 +1,380 @@
+//! JSON Schema and OpenAPI descriptions of IDs.
+
+use std::borrow::Cow;
+
+use crate::{encode, ShortId, MAX_BYTES};
+
+/// Random-looking bytes the example IDs in schemas are encoded from.
+const EXAMPLE_BYTES: [u8; MAX_BYTES] = [
+    0x5e, 0xc4, 0x2b, 0x91, 0x7f, 0x08, 0xd3, 0x66, 0xa1, 0x3c, 0xe8, 0x47, 0x12, 0xb9, 0x70, 0x2d,
+    0x95, 0xfa, 0x4e, 0x03, 0xc7, 0x88, 0x39, 0x6b, 0xde, 0x14, 0xa5, 0x52, 0xf0, 0x8e, 0x27, 0xbb,
+];
+
+/// Character class of the ID alphabet.
+const CHAR: &str = "[-0-9A-Z_a-z]";
+
+/// Returns the length of the encoding of `num_bytes` bytes.
+const fn encoded_len(num_bytes: usize) -> usize {
+    (num_bytes * 4).div_ceil(3)
+}
+
+/// The string format of a kind of ID, for describing it in API schemas.
+///
+/// [`ShortId`] is described as any encoding of 1 to 32 bytes, with a default 14-character
+/// ID as the example. Typed ID newtypes can describe themselves more precisely, with a
+/// fixed prefix and the number of bytes they are generated from, by implementing
+/// `JsonSchema` or `ToSchema` with their own `IdFormat`. The schema then carries:
+///
+/// - `pattern`: the prefix followed by characters from the ID alphabet, in only the
+///   lengths an encoding of the allowed byte counts can have
+/// - `minLength` / `maxLength`: the length of the prefix plus the encoded bytes
+/// - `examples`: a valid ID in the format
+///
+/// **This type requires the `schemars` or `utoipa` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::IdFormat;
+///
+/// const USER_ID: IdFormat = IdFormat::new().with_prefix("usr_").with_bytes(10);
+///
+/// assert_eq!(USER_ID.pattern(), "^usr_[-0-9A-Z_a-z]{14}$");
+/// assert_eq!(USER_ID.min_length(), 18);
+/// assert_eq!(USER_ID.max_length(), 18);
+/// assert!(USER_ID.example().starts_with("usr_"));
+/// ```
+///
+/// Using it for a newtype with `schemars`:
+///
+/// ```
+/// # #[cfg(feature = "schemars")]
+/// # {
+/// use schemars::{JsonSchema, Schema, SchemaGenerator};
+/// use short_id::{IdFormat, ShortId};
+/// use std::borrow::Cow;
+///
+/// struct UserId(ShortId);
+///
+/// impl JsonSchema for UserId {
+///     fn schema_name() -> Cow<'static, str> {
+///         "UserId".into()
+///     }
+///
+///     fn json_schema(_: &mut SchemaGenerator) -> Schema {
+///         IdFormat::new().with_prefix("usr_").with_bytes(10).json_schema()
+///     }
+/// }
+///
+/// let schema = schemars::schema_for!(UserId);
+/// assert_eq!(schema.get("pattern").unwrap(), "^usr_[-0-9A-Z_a-z]{14}$");
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdFormat {
+    prefix: &'static str,
+    min_bytes: usize,
+    max_bytes: usize,
+}
+
+impl IdFormat {
+    /// The format of a [`ShortId`]: no prefix and 1 to 32 bytes.
+    pub const SHORT_ID: IdFormat = IdFormat {
+        prefix: "",
+        min_bytes: 1,
+        max_bytes: MAX_BYTES,
+    };
+
+    /// Creates the format of a [`ShortId`]. Equivalent to [`IdFormat::SHORT_ID`].
+    pub const fn new() -> Self {
+        Self::SHORT_ID
+    }
+
+    /// Sets a fixed prefix that comes before the ID, such as `usr_`.
+    pub const fn with_prefix(mut self, prefix: &'static str) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Sets the exact number of bytes the IDs are generated from, e.g. 10 for
+    /// [`short_id()`](crate::short_id).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is 0.
+    pub const fn with_bytes(self, num_bytes: usize) -> Self {
+        self.with_bytes_range(num_bytes, num_bytes)
+    }
+
+    /// Sets the range of the number of bytes the IDs are generated from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_bytes` is 0 or greater than `max_bytes`.
+    pub const fn with_bytes_range(mut self, min_bytes: usize, max_bytes: usize) -> Self {
+        assert!(min_bytes > 0, "num_bytes must be greater than 0");
+        assert!(
+            min_bytes <= max_bytes,
+            "min_bytes must not exceed max_bytes"
+        );
+        self.min_bytes = min_bytes;
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Returns the prefix.
+    pub const fn prefix(&self) -> &'static str {
+        self.prefix
+    }
+
+    /// Returns the length of the shortest ID in the format, including the prefix.
+    pub const fn min_length(&self) -> usize {
+        self.prefix.len() + encoded_len(self.min_bytes)
+    }
+
+    /// Returns the length of the longest ID in the format, including the prefix.
+    pub const fn max_length(&self) -> usize {
+        self.prefix.len() + encoded_len(self.max_bytes)
+    }
+
+    /// Returns a regular expression matching IDs in the format.
+    ///
+    /// The expression uses only syntax shared by ECMA-262 (which JSON Schema specifies)
+    /// and common regex engines. It accepts exactly the lengths the allowed byte counts
+    /// encode to: every 3 bytes take 4 characters and 1 or 2 leftover bytes take 2 or 3,
+    /// so no ID is 1 more than a multiple of 4 characters long. It does not check that
+    /// the unused low bits of the last character are zero, which
+    /// [`ShortId::parse()`](crate::ShortId::parse) also rejects.
+    pub fn pattern(&self) -> String {
+        let mut pattern = String::from("^");
+        for c in self.prefix.chars() {
+            if "\\^$.|?*+()[]{}/".contains(c) {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        if self.min_bytes == self.max_bytes {
+            pattern.push_str(&format!("{}{{{}}}$", CHAR, encoded_len(self.min_bytes)));
+            return pattern;
+        }
+
+        // One alternative per number of leftover bytes after the last full 3-byte group
+        let mut alternatives = Vec::new();
+        for (leftover, tail) in [(0, 0), (1, 2), (2, 3)] {
+            let Some(max_groups) = self.max_bytes.checked_sub(leftover).map(|n| n / 3) else {
+                continue;
+            };
+            let min_groups = self.min_bytes.saturating_sub(leftover).div_ceil(3);
+            let min_groups = if tail == 0 {
+                min_groups.max(1)
+            } else {
+                min_groups
+            };
+            if min_groups > max_groups {
+                continue;
+            }
+            let mut alternative = String::new();
+            if min_groups == max_groups && max_groups > 0 {
+                alternative.push_str(&format!("(?:{}{{4}}){{{}}}", CHAR, min_groups));
+            } else if min_groups < max_groups {
+                alternative.push_str(&format!(
+                    "(?:{}{{4}}){{{},{}}}",
+                    CHAR, min_groups, max_groups
+                ));
+            }
+            if tail > 0 {
+                alternative.push_str(&format!("{}{{{}}}", CHAR, tail));
+            }
+            alternatives.push(alternative);
+        }
+        pattern.push_str(&format!("(?:{})$", alternatives.join("|")));
+        pattern
+    }
+
+    /// Returns an example ID in the format.
+    ///
+    /// The example is 10 bytes (14 characters) long if the format allows it, the default
+    /// length of [`short_id()`](crate::short_id), and the shortest allowed length
+    /// otherwise. Its bytes are fixed, so generated documents don't change between runs.
+    pub fn example(&self) -> String {
+        let num_bytes = if (self.min_bytes..=self.max_bytes).contains(&10) {
+            10
+        } else {
+            self.min_bytes
+        };
+        let mut bytes = EXAMPLE_BYTES.to_vec();
+        bytes.resize(num_bytes, 0x5e);
+        let mut example = String::from(self.prefix);
+        example.push_str(&encode(&bytes));
+        example
+    }
+
+    /// Returns the JSON Schema of the format.
+    ///
+    /// **This method requires the `schemars` feature.**
+    #[cfg(feature = "schemars")]
+    pub fn json_schema(&self) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": self.pattern(),
+            "minLength": self.min_length(),
+            "maxLength": self.max_length(),
+            "examples": [self.example()],
+        })
+    }
+
+    /// Returns the OpenAPI schema of the format.
+    ///
+    /// **This method requires the `utoipa` feature.**
+    #[cfg(feature = "utoipa")]
+    pub fn openapi_schema(&self) -> utoipa::openapi::schema::Schema {
+        use utoipa::openapi::schema::{ObjectBuilder, Type};
+
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .pattern(Some(self.pattern()))
+            .min_length(Some(self.min_length()))
+            .max_length(Some(self.max_length()))
+            .examples([self.example()])
+            .build()
+            .into()
+    }
+}
+
+impl Default for IdFormat {
+    fn default() -> Self {
+        Self::SHORT_ID
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for ShortId {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::Borrowed("ShortId")
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        Cow::Borrowed("short_id::ShortId")
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        IdFormat::SHORT_ID.json_schema()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl utoipa::PartialSchema for ShortId {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        IdFormat::SHORT_ID.openapi_schema().into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl utoipa::ToSchema for ShortId {
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("ShortId")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_id_format() {
+        let format = IdFormat::SHORT_ID;
+        assert_eq!(
+            format.pattern(),
+            "^(?:(?:[-0-9A-Z_a-z]{4}){1,10}\
+             |(?:[-0-9A-Z_a-z]{4}){0,10}[-0-9A-Z_a-z]{2}\
+             |(?:[-0-9A-Z_a-z]{4}){0,10}[-0-9A-Z_a-z]{3})$"
+        );
+        assert_eq!((format.min_length(), format.max_length()), (2, 43));
+
+        let example = format.example();
+        assert_eq!(example.len(), 14);
+        assert!(ShortId::parse(&example).is_ok());
+    }
+
+    #[test]
+    fn test_prefixed_format() {
+        let format = IdFormat::new().with_prefix("usr_").with_bytes(16);
+        assert_eq!(format.pattern(), "^usr_[-0-9A-Z_a-z]{22}$");
+        assert_eq!((format.min_length(), format.max_length()), (26, 26));
+
+        let example = format.example();
+        assert_eq!(example.len(), 26);
+        assert!(ShortId::parse(&example[4..]).is_ok());
+    }
+
+    #[test]
+    fn test_prefix_is_escaped() {
+        let format = IdFormat::new().with_prefix("a.b/").with_bytes_range(8, 12);
+        assert_eq!(
+            format.pattern(),
+            "^a\\.b\\/(?:(?:[-0-9A-Z_a-z]{4}){3,4}\
+             |(?:[-0-9A-Z_a-z]{4}){3}[-0-9A-Z_a-z]{2}\
+             |(?:[-0-9A-Z_a-z]{4}){2,3}[-0-9A-Z_a-z]{3})$"
+        );
+        assert_eq!(format.example().len(), 4 + 14);
+    }
+
+    #[test]
+    fn test_pattern_for_small_ranges() {
+        let format = IdFormat::new().with_bytes_range(1, 2);
+        assert_eq!(format.pattern(), "^(?:[-0-9A-Z_a-z]{2}|[-0-9A-Z_a-z]{3})$");
+        let format = IdFormat::new().with_bytes_range(3, 4);
+        assert_eq!(
+            format.pattern(),
+            "^(?:(?:[-0-9A-Z_a-z]{4}){1}|(?:[-0-9A-Z_a-z]{4}){1}[-0-9A-Z_a-z]{2})$"
+        );
+    }
+
+    #[test]
+    fn test_example_uses_shortest_length_if_10_bytes_is_out_of_range() {
+        let format = IdFormat::new().with_bytes(16);
+        assert_eq!(format.example().len(), 22);
+        let format = IdFormat::new().with_bytes_range(4, 6);
+        assert_eq!(format.example().len(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "min_bytes must not exceed max_bytes")]
+    fn test_inverted_range_panics() {
+        IdFormat::new().with_bytes_range(12, 8);
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() {
+        let schema = schemars::schema_for!(ShortId);
+        assert_eq!(schema.get("type").unwrap(), "string");
+        assert_eq!(
+            schema.get("pattern").unwrap(),
+            &IdFormat::SHORT_ID.pattern()
+        );
+        assert_eq!(schema.get("minLength").unwrap(), 2);
+        assert_eq!(schema.get("maxLength").unwrap(), 43);
+        assert_eq!(
+            schema.get("examples").unwrap()[0],
+            IdFormat::SHORT_ID.example()
+        );
+    }
+
+    #[cfg(feature = "utoipa")]
+    #[test]
+    fn test_openapi_schema() {
+        use utoipa::openapi::{schema::Schema, RefOr};
+        use utoipa::{PartialSchema, ToSchema};
+
+        assert_eq!(<ShortId as ToSchema>::name(), "ShortId");
+        let object = match ShortId::schema() {
+            RefOr::T(Schema::Object(object)) => object,
+            _ => panic!("expected an object schema"),
+        };
+        assert_eq!(object.pattern, Some(IdFormat::SHORT_ID.pattern()));
+        assert_eq!(object.min_length, Some(2));
+        assert_eq!(object.max_length, Some(43));
+        assert_eq!(object.examples.len(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+//! IDs used as secrets, such as session tokens.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec};
+
+use rand::{rngs::OsRng, RngCore};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{encode, ShortId, MAX_BYTES};
+
+/// An ID that must be kept secret, such as a session token or password reset code.
+///
+/// [`ShortId`] is meant to be shown: it implements `Display`, and its `Debug` output
+/// includes the ID. `SecretId` guards against accidental disclosure instead:
+///
+/// - `Debug` prints `SecretId(***)`, and there is no `Display`
+/// - the value is only available through [`expose_secret()`](Self::expose_secret), which
+///   makes every use easy to find in review
+/// - comparisons run in constant time, so response timing doesn't reveal how many
+///   leading characters of a guess were right
+/// - the buffer is overwritten with zeros when the `SecretId` is dropped, or earlier
+///   with [`Zeroize::zeroize()`](zeroize::Zeroize::zeroize)
+///
+/// **This type requires the `secret` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::SecretId;
+///
+/// let session = SecretId::random();
+/// assert_eq!(format!("{:?}", session), "SecretId(***)");
+///
+/// // Explicitly expose the value to set the cookie
+/// let cookie = format!("session={}", session.expose_secret());
+///
+/// // Compare presented tokens in constant time
+/// let presented = SecretId::from(String::from(session.expose_secret()));
+/// assert_eq!(presented, session);
+/// ```
+#[derive(Clone)]
+pub struct SecretId(String);
+
+impl SecretId {
+    /// Generates a random secret ID with 16 bytes (128 bits) of entropy, encoded as 22
+    /// characters.
+    ///
+    /// Secrets get more entropy than the 10 bytes of [`short_id()`](crate::short_id),
+    /// since an attacker who guesses one gains access rather than just a name.
+    pub fn random() -> Self {
+        SecretId::with_bytes(16)
+    }
+
+    /// Generates a random secret ID from `num_bytes` random bytes.
+    ///
+    /// The intermediate random bytes are zeroized as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bytes` is 0 or exceeds 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::SecretId;
+    ///
+    /// let token = SecretId::with_bytes(24);
+    /// assert_eq!(token.expose_secret().len(), 32);
+    /// ```
+    pub fn with_bytes(num_bytes: usize) -> Self {
+        assert!(num_bytes > 0, "num_bytes must be greater than 0");
+        assert!(
+            num_bytes <= MAX_BYTES,
+            "num_bytes must not exceed {} (got {})",
+            MAX_BYTES,
+            num_bytes
+        );
+
+        let mut bytes = vec![0u8; num_bytes];
+        OsRng.fill_bytes(&mut bytes);
+        let secret = SecretId(encode(&bytes));
+        bytes.zeroize();
+        secret
+    }
+
+    /// Returns the secret value.
+    ///
+    /// Keep the returned string out of logs and error messages.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretId {
+    /// Takes ownership of `secret` without copying it, so no unzeroized copy remains.
+    fn from(secret: String) -> Self {
+        SecretId(secret)
+    }
+}
+
+impl From<ShortId> for SecretId {
+    fn from(id: ShortId) -> Self {
+        SecretId(id.0)
+    }
+}
+
+impl PartialEq for SecretId {
+    /// Compares in constant time with respect to the contents.
+    ///
+    /// Only the lengths of the two values can influence the running time.
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_bytes().ct_eq(other.0.as_bytes()).into()
+    }
+}
+
+impl Eq for SecretId {}
+
+impl core::fmt::Debug for SecretId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SecretId(***)")
+    }
+}
+
+impl Zeroize for SecretId {
+    /// Overwrites the buffer with zeros and leaves the secret empty.
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SecretId {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretId {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::format;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = SecretId::random();
+        assert_eq!(format!("{:?}", secret), "SecretId(***)");
+        assert_eq!(
+            format!("{:#?}", Some(secret)),
+            "Some(\n    SecretId(***),\n)"
+        );
+    }
+
+    #[test]
+    fn test_random_secrets() {
+        let secret = SecretId::random();
+        assert_eq!(secret.expose_secret().len(), 22);
+        assert_ne!(secret, SecretId::random());
+    }
+
+    #[test]
+    fn test_equality() {
+        let secret = SecretId::from(String::from("abcdef"));
+        assert_eq!(secret, SecretId::from(String::from("abcdef")));
+        assert_ne!(secret, SecretId::from(String::from("abcdeg")));
+        assert_ne!(secret, SecretId::from(String::from("abcde")));
+        assert_eq!(secret.clone(), secret);
+    }
+
+    #[test]
+    fn test_from_short_id() {
+        let id = ShortId::random();
+        let expected = String::from(id.as_str());
+        assert_eq!(SecretId::from(id).expose_secret(), expected);
+    }
+
+    #[test]
+    fn test_zeroizes_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<SecretId>();
+
+        // Drop runs the same zeroize() as this explicit call
+        let mut secret = SecretId::random();
+        let capacity = secret.0.capacity();
+        secret.zeroize();
+        assert_eq!(secret.expose_secret(), "");
+        assert_eq!(secret.0.capacity(), capacity);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_bytes must be greater than 0")]
+    fn test_zero_bytes_panics() {
+        SecretId::with_bytes(0);
+    }
+}
@@ -0,0 +1,382 @@
+//! API keys and other secret tokens in a scanner-friendly format.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use rand::{rngs::OsRng, RngCore};
+use subtle::ConstantTimeEq;
+
+/// Characters used for the random part and checksum, in ASCII order.
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Number of random base62 characters: 30 × log2(62) ≈ 178 bits.
+const RANDOM_LEN: usize = 30;
+
+/// Number of base62 characters holding the CRC32 checksum (62⁶ > 2³²).
+const CHECKSUM_LEN: usize = 6;
+
+/// Maximum length of a vendor prefix.
+const MAX_PREFIX_LEN: usize = 16;
+
+/// A secret token such as an API key, in a format secret scanners can recognize.
+///
+/// Tokens look like `acme_1Xb8ZqP0xQm2VnW7cR4kT9sLh3JdYf0Gz0T5`, in the style of GitHub's
+/// tokens:
+///
+/// - a fixed vendor prefix and `_`, which identify the issuer
+/// - 30 random base62 characters (about 178 bits of entropy)
+/// - 6 base62 characters encoding the CRC32 of everything before them
+///
+/// A scanner matching `acme_[0-9A-Za-z]{36}` can call
+/// [`validate_format()`](Self::validate_format) to discard random strings that happen
+/// to match, without a network call: only about one in four billion has a valid checksum.
+/// The checksum only detects accidents; it says nothing about whether the token was
+/// ever issued.
+///
+/// The `Debug` output shows only the prefix, so tokens don't end up in logs by accident,
+/// and tokens compare in constant time.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::SecretToken;
+///
+/// let token = SecretToken::generate("acme");
+/// assert!(token.as_str().starts_with("acme_"));
+/// assert_eq!(token.as_str().len(), 5 + 36);
+///
+/// // Scanners confirm candidates offline
+/// assert!(SecretToken::validate_format(token.as_str(), "acme").is_ok());
+/// assert!(SecretToken::validate_format("acme_000000000000000000000000000000000000", "acme").is_err());
+///
+/// assert_eq!(format!("{:?}", token), "SecretToken(acme_***)");
+/// ```
+#[derive(Clone)]
+pub struct SecretToken {
+    token: String,
+    prefix_len: usize,
+}
+
+impl SecretToken {
+    /// Generates a token with the vendor prefix `prefix`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` is empty, longer than 16 characters, or contains characters
+    /// other than ASCII letters and digits.
+    pub fn generate(prefix: &str) -> Self {
+        assert!(
+            is_valid_prefix(prefix),
+            "prefix must be 1 to {} ASCII letters or digits",
+            MAX_PREFIX_LEN
+        );
+
+        let mut token = String::with_capacity(prefix.len() + 1 + RANDOM_LEN + CHECKSUM_LEN);
+        token.push_str(prefix);
+        token.push('_');
+        let mut remaining = RANDOM_LEN;
+        let mut buf = [0u8; RANDOM_LEN];
+        while remaining > 0 {
+            OsRng.fill_bytes(&mut buf);
+            // Rejection sampling keeps every character equally likely
+            for &byte in buf.iter().filter(|&&byte| byte < 248).take(remaining) {
+                token.push(BASE62[(byte % 62) as usize] as char);
+                remaining -= 1;
+            }
+        }
+        push_checksum(&mut token);
+
+        SecretToken {
+            token,
+            prefix_len: prefix.len(),
+        }
+    }
+
+    /// Checks that `token` is a well-formed token with the vendor prefix `prefix` and a
+    /// valid checksum.
+    ///
+    /// # Errors
+    ///
+    /// - [`SecretTokenError::InvalidPrefix`] if `token` does not start with `prefix`
+    ///   followed by `_`
+    /// - [`SecretTokenError::InvalidLength`] if the part after the prefix is not 36
+    ///   characters long
+    /// - [`SecretTokenError::InvalidCharacter`] if it contains a character outside
+    ///   `[0-9A-Za-z]`
+    /// - [`SecretTokenError::InvalidChecksum`] if the checksum doesn't match
+    pub fn validate_format(token: &str, prefix: &str) -> Result<(), SecretTokenError> {
+        let body = token
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix('_'))
+            .filter(|_| is_valid_prefix(prefix))
+            .ok_or(SecretTokenError::InvalidPrefix)?;
+        if let Some(c) = body.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(SecretTokenError::InvalidCharacter(c));
+        }
+        if body.len() != RANDOM_LEN + CHECKSUM_LEN {
+            return Err(SecretTokenError::InvalidLength(body.len()));
+        }
+
+        let (checked, checksum) = token.split_at(token.len() - CHECKSUM_LEN);
+        if encode_checksum(crc32(checked.as_bytes())) != checksum.as_bytes() {
+            return Err(SecretTokenError::InvalidChecksum);
+        }
+        Ok(())
+    }
+
+    /// Parses a token with the vendor prefix `prefix`, validating its format.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`validate_format()`](Self::validate_format).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::SecretToken;
+    ///
+    /// let issued = SecretToken::generate("acme");
+    /// let presented = SecretToken::parse(issued.as_str(), "acme").unwrap();
+    /// assert_eq!(presented, issued);
+    /// ```
+    pub fn parse(token: &str, prefix: &str) -> Result<Self, SecretTokenError> {
+        SecretToken::validate_format(token, prefix)?;
+        Ok(SecretToken {
+            token: String::from(token),
+            prefix_len: prefix.len(),
+        })
+    }
+
+    /// Returns the vendor prefix, without the `_`.
+    pub fn prefix(&self) -> &str {
+        &self.token[..self.prefix_len]
+    }
+
+    /// Returns the full token.
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    /// Consumes the token and returns the inner `String`.
+    pub fn into_string(self) -> String {
+        self.token
+    }
+}
+
+impl PartialEq for SecretToken {
+    /// Compares in constant time with respect to the contents.
+    ///
+    /// Only the lengths of the two tokens can influence the running time.
+    fn eq(&self, other: &Self) -> bool {
+        self.token.as_bytes().ct_eq(other.token.as_bytes()).into()
+    }
+}
+
+impl Eq for SecretToken {}
+
+impl core::hash::Hash for SecretToken {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.token.hash(state);
+    }
+}
+
+impl core::fmt::Debug for SecretToken {
+    /// Shows only the prefix.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SecretToken({}_***)", self.prefix())
+    }
+}
+
+/// Error returned when a string is not a well-formed [`SecretToken`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SecretTokenError {
+    /// The string does not start with the expected vendor prefix and `_`.
+    InvalidPrefix,
+    /// The part after the prefix has the contained length instead of 36 characters.
+    InvalidLength(usize),
+    /// The part after the prefix contains a character outside `[0-9A-Za-z]`.
+    InvalidCharacter(char),
+    /// The CRC32 checksum does not match.
+    InvalidChecksum,
+}
+
+impl core::fmt::Display for SecretTokenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SecretTokenError::InvalidPrefix => write!(f, "invalid secret token prefix"),
+            SecretTokenError::InvalidLength(len) => {
+                write!(f, "invalid secret token length ({} characters)", len)
+            }
+            SecretTokenError::InvalidCharacter(c) => {
+                write!(f, "invalid secret token character {:?}", c)
+            }
+            SecretTokenError::InvalidChecksum => write!(f, "invalid secret token checksum"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SecretTokenError {}
+
+/// Returns `true` if `prefix` is 1 to 16 ASCII letters or digits.
+fn is_valid_prefix(prefix: &str) -> bool {
+    (1..=MAX_PREFIX_LEN).contains(&prefix.len())
+        && prefix.bytes().all(|byte| byte.is_ascii_alphanumeric())
+}
+
+/// Appends the checksum of `token` to it.
+fn push_checksum(token: &mut String) {
+    let checksum = encode_checksum(crc32(token.as_bytes()));
+    token.extend(checksum.iter().map(|&byte| byte as char));
+}
+
+/// Encodes a CRC32 value as 6 base62 characters, most significant first.
+fn encode_checksum(mut crc: u32) -> [u8; CHECKSUM_LEN] {
+    let mut encoded = [BASE62[0]; CHECKSUM_LEN];
+    for slot in encoded.iter_mut().rev() {
+        *slot = BASE62[(crc % 62) as usize];
+        crc /= 62;
+    }
+    encoded
+}
+
+/// Lookup table for the reflected CRC-32 (IEEE 802.3) polynomial `0xEDB88320`.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32 (IEEE 802.3, as used by zlib and PNG) of `bytes`.
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::format;
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_checksum_encoding() {
+        assert_eq!(&encode_checksum(0), b"000000");
+        assert_eq!(&encode_checksum(61), b"00000z");
+        assert_eq!(&encode_checksum(u32::MAX), b"4gfFC3");
+    }
+
+    #[test]
+    fn test_generated_tokens_validate() {
+        for _ in 0..100 {
+            let token = SecretToken::generate("acme");
+            assert_eq!(token.as_str().len(), 41);
+            assert_eq!(token.prefix(), "acme");
+            assert_eq!(SecretToken::validate_format(token.as_str(), "acme"), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_random_part_uses_whole_alphabet() {
+        let mut seen = [false; 62];
+        for _ in 0..100 {
+            let token = SecretToken::generate("t");
+            for byte in token.as_str()[2..2 + RANDOM_LEN].bytes() {
+                seen[BASE62.iter().position(|&c| c == byte).unwrap()] = true;
+            }
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+
+    #[test]
+    fn test_detects_single_character_changes() {
+        let token = SecretToken::generate("acme").into_string();
+        for position in 5..token.len() {
+            let mut typo = token.clone().into_bytes();
+            typo[position] = if typo[position] == b'a' { b'b' } else { b'a' };
+            let typo = String::from_utf8(typo).unwrap();
+            assert_eq!(
+                SecretToken::validate_format(&typo, "acme"),
+                Err(SecretTokenError::InvalidChecksum)
+            );
+        }
+    }
+
+    #[test]
+    fn test_checksum_covers_prefix() {
+        let token = SecretToken::generate("acme");
+        let moved = token.as_str().replacen("acme", "acmf", 1);
+        assert_eq!(
+            SecretToken::validate_format(&moved, "acmf"),
+            Err(SecretTokenError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_tokens() {
+        let token = SecretToken::generate("acme").into_string();
+        assert_eq!(
+            SecretToken::validate_format(&token, "other"),
+            Err(SecretTokenError::InvalidPrefix)
+        );
+        assert_eq!(
+            SecretToken::validate_format(&token.replacen('_', "-", 1), "acme"),
+            Err(SecretTokenError::InvalidPrefix)
+        );
+        assert_eq!(
+            SecretToken::validate_format(&token[..40], "acme"),
+            Err(SecretTokenError::InvalidLength(35))
+        );
+        assert_eq!(
+            SecretToken::validate_format(&format!("{}!", &token[..40]), "acme"),
+            Err(SecretTokenError::InvalidCharacter('!'))
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_secret() {
+        let token = SecretToken::generate("acme");
+        assert_eq!(format!("{:?}", token), "SecretToken(acme_***)");
+        assert_eq!(
+            SecretToken::parse(token.as_str(), "acme").unwrap().prefix(),
+            "acme"
+        );
+    }
+
+    #[test]
+    fn test_equality() {
+        let token = SecretToken::generate("acme");
+        assert_eq!(SecretToken::parse(token.as_str(), "acme").unwrap(), token);
+        assert_ne!(SecretToken::generate("acme"), token);
+        assert_eq!(token.clone(), token);
+    }
+
+    #[test]
+    #[should_panic(expected = "prefix must be 1 to 16 ASCII letters or digits")]
+    fn test_invalid_prefix_panics() {
+        SecretToken::generate("acme_");
+    }
+}
@@ -0,0 +1,412 @@
+//! HMAC-signed, tamper-evident IDs.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+
+use crate::{decode, encode, DecodeError, ShortId};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Number of random payload bytes in a generated ID, matching [`short_id()`](crate::short_id).
+const PAYLOAD_BYTES: usize = 10;
+
+/// Generator and verifier of IDs carrying a truncated HMAC-SHA256 tag.
+///
+/// IDs in unauthenticated links (email unsubscribe, invites, password resets) are
+/// entered by strangers. Signing them lets a service reject forged or mutated IDs
+/// before touching the database. A signed ID is laid out as:
+///
+/// - 1 byte: key ID, selecting the key that signed it
+/// - the payload: 10 random bytes for [`generate()`](Self::generate), or the bytes of
+///   the ID passed to [`sign()`](Self::sign)
+/// - 8 bytes (configurable): HMAC-SHA256 of the key ID and payload, truncated
+///
+/// so a generated ID is 19 bytes, or 26 characters. [`verify()`](Self::verify) checks the
+/// tag in constant time and returns the payload as a [`ShortId`], which is what you
+/// store and look up.
+///
+/// # Key rotation
+///
+/// A generator signs with one key and verifies with every key it knows. To rotate, add
+/// the new key with [`with_signing_key()`](Self::with_signing_key) and keep the old
+/// one with [`with_key()`](Self::with_key) until the IDs it signed have expired.
+///
+/// **This type requires the `signed` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{SignedIdGenerator, VerifyError};
+///
+/// let signer = SignedIdGenerator::new(b"a secret key from configuration");
+///
+/// let link_id = signer.generate();
+/// assert_eq!(link_id.as_str().len(), 26);
+///
+/// let payload = signer.verify(link_id.as_str()).unwrap();
+/// assert_eq!(payload.as_str().len(), 14);
+///
+/// // Changing any character invalidates the ID
+/// let mut forged = String::from(link_id.as_str());
+/// let replacement = if &forged[12..13] == "A" { "B" } else { "A" };
+/// forged.replace_range(12..13, replacement);
+/// assert_eq!(signer.verify(&forged), Err(VerifyError::InvalidSignature));
+/// ```
+#[derive(Clone)]
+pub struct SignedIdGenerator {
+    keys: Vec<(u8, HmacSha256)>,
+    signing_key: u8,
+    tag_bytes: usize,
+}
+
+impl SignedIdGenerator {
+    /// Creates a generator that signs and verifies with `key`, under key ID 0.
+    ///
+    /// Use a random key of at least 32 bytes.
+    pub fn new(key: &[u8]) -> Self {
+        SignedIdGenerator {
+            keys: Vec::new(),
+            signing_key: 0,
+            tag_bytes: 8,
+        }
+        .with_signing_key(0, key)
+    }
+
+    /// Adds `key` under `key_id` for verification only, e.g. a retired key whose IDs
+    /// are still in circulation.
+    ///
+    /// Replaces any key already registered under `key_id`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::SignedIdGenerator;
+    ///
+    /// let old = SignedIdGenerator::new(b"old key");
+    /// let id = old.generate();
+    ///
+    /// // Sign new IDs with key 1, but keep accepting IDs signed with key 0
+    /// let rotated = SignedIdGenerator::new(b"old key")
+    ///     .with_signing_key(1, b"new key");
+    /// assert!(rotated.verify(id.as_str()).is_ok());
+    /// assert_eq!(rotated.generate().to_bytes().unwrap()[0], 1);
+    /// ```
+    pub fn with_key(mut self, key_id: u8, key: &[u8]) -> Self {
+        let mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+        self.keys.retain(|(id, _)| *id != key_id);
+        self.keys.push((key_id, mac));
+        self
+    }
+
+    /// Adds `key` under `key_id` and signs new IDs with it.
+    ///
+    /// Previously added keys stay available for verification.
+    pub fn with_signing_key(mut self, key_id: u8, key: &[u8]) -> Self {
+        self = self.with_key(key_id, key);
+        self.signing_key = key_id;
+        self
+    }
+
+    /// Sets the number of HMAC bytes kept in each ID (8 by default).
+    ///
+    /// Longer tags make forgery harder at the cost of longer IDs; each byte adds 8 bits
+    /// of security. Changing the length invalidates IDs signed with the previous length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag_bytes` is less than 4 or greater than 32.
+    pub fn with_tag_bytes(mut self, tag_bytes: usize) -> Self {
+        assert!(
+            (4..=32).contains(&tag_bytes),
+            "tag_bytes must be between 4 and 32"
+        );
+        self.tag_bytes = tag_bytes;
+        self
+    }
+
+    /// Returns the ID of the key new IDs are signed with.
+    pub fn signing_key_id(&self) -> u8 {
+        self.signing_key
+    }
+
+    /// Returns the number of HMAC bytes in each ID.
+    pub fn tag_bytes(&self) -> usize {
+        self.tag_bytes
+    }
+
+    /// Generates a signed ID with a random 10-byte payload.
+    pub fn generate(&self) -> ShortId {
+        let mut payload = [0u8; PAYLOAD_BYTES];
+        OsRng.fill_bytes(&mut payload);
+        ShortId(self.sign_bytes(&payload))
+    }
+
+    /// Signs an existing ID, e.g. the primary key of the record a link points to.
+    ///
+    /// [`verify()`](Self::verify) returns `id` again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it is empty, since [`verify()`](Self::verify)
+    /// rejects signed IDs without a payload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{ShortId, SignedIdGenerator};
+    ///
+    /// let signer = SignedIdGenerator::new(b"a secret key from configuration");
+    /// let invite = ShortId::random();
+    ///
+    /// let link_id = signer.sign(&invite).unwrap();
+    /// assert_eq!(signer.verify(&link_id), Ok(invite));
+    /// ```
+    pub fn sign(&self, id: &ShortId) -> Result<String, DecodeError> {
+        let payload = id.to_bytes()?;
+        if payload.is_empty() {
+            return Err(DecodeError::InvalidLength(0));
+        }
+        Ok(self.sign_bytes(&payload))
+    }
+
+    /// Verifies a signed ID and returns its payload.
+    ///
+    /// # Errors
+    ///
+    /// - [`VerifyError::Decode`] if `signed` is not a valid ID string, or too short to
+    ///   hold a key ID, a payload and a tag
+    /// - [`VerifyError::UnknownKey`] if it was signed with a key this generator doesn't
+    ///   know
+    /// - [`VerifyError::InvalidSignature`] if the tag doesn't match
+    pub fn verify(&self, signed: &str) -> Result<ShortId, VerifyError> {
+        let bytes = decode(signed)?;
+        if bytes.len() < 1 + 1 + self.tag_bytes {
+            return Err(DecodeError::InvalidLength(bytes.len()).into());
+        }
+        let (message, tag) = bytes.split_at(bytes.len() - self.tag_bytes);
+        let key_id = message[0];
+
+        let mut mac = self
+            .key(key_id)
+            .ok_or(VerifyError::UnknownKey(key_id))?
+            .clone();
+        mac.update(message);
+        mac.verify_truncated_left(tag)
+            .map_err(|_| VerifyError::InvalidSignature)?;
+
+        Ok(ShortId::from_bytes(&message[1..]))
+    }
+
+    /// Prefixes `payload` with the signing key ID and appends the tag.
+    pub(crate) fn sign_bytes(&self, payload: &[u8]) -> String {
+        let mut bytes = Vec::with_capacity(1 + payload.len() + self.tag_bytes);
+        bytes.push(self.signing_key);
+        bytes.extend_from_slice(payload);
+
+        let mut mac = self
+            .key(self.signing_key)
+            .expect("signing key is registered")
+            .clone();
+        mac.update(&bytes);
+        bytes.extend_from_slice(&mac.finalize().into_bytes()[..self.tag_bytes]);
+        encode(&bytes)
+    }
+
+    /// Looks up the MAC initialized with the key registered under `key_id`.
+    fn key(&self, key_id: u8) -> Option<&HmacSha256> {
+        self.keys
+            .iter()
+            .find(|(id, _)| *id == key_id)
+            .map(|(_, mac)| mac)
+    }
+}
+
+impl core::fmt::Debug for SignedIdGenerator {
+    /// Lists the key IDs but omits the key material.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let key_ids: Vec<u8> = self.keys.iter().map(|(id, _)| *id).collect();
+        f.debug_struct("SignedIdGenerator")
+            .field("key_ids", &key_ids)
+            .field("signing_key", &self.signing_key)
+            .field("tag_bytes", &self.tag_bytes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Error returned when a signed ID fails verification.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{SignedIdGenerator, VerifyError};
+///
+/// let signer = SignedIdGenerator::new(b"key");
+/// let other = SignedIdGenerator::new(b"key").with_signing_key(7, b"other key");
+///
+/// let id = other.generate();
+/// assert_eq!(signer.verify(id.as_str()), Err(VerifyError::UnknownKey(7)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// The string is not a valid ID, or is too short to be a signed one.
+    Decode(DecodeError),
+    /// The ID names a key ID the verifier has no key for.
+    UnknownKey(u8),
+    /// The tag does not match: the ID was forged, mutated, or signed with a different
+    /// key under the same key ID.
+    InvalidSignature,
+}
+
+impl From<DecodeError> for VerifyError {
+    fn from(error: DecodeError) -> Self {
+        VerifyError::Decode(error)
+    }
+}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VerifyError::Decode(error) => write!(f, "{}", error),
+            VerifyError::UnknownKey(key_id) => write!(f, "unknown signing key ID {}", key_id),
+            VerifyError::InvalidSignature => write!(f, "invalid short ID signature"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifyError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::{format, vec};
+
+    #[test]
+    fn test_generate_and_verify() {
+        let signer = SignedIdGenerator::new(b"key");
+        let signed = signer.generate();
+        assert_eq!(signed.as_str().len(), 26);
+
+        let payload = signer.verify(signed.as_str()).unwrap();
+        assert_eq!(payload.to_bytes().unwrap().len(), PAYLOAD_BYTES);
+        assert_eq!(signer.sign(&payload).unwrap(), signed.as_str());
+    }
+
+    #[test]
+    fn test_every_single_byte_change_is_rejected() {
+        let signer = SignedIdGenerator::new(b"key");
+        let bytes = signer.generate().to_bytes().unwrap();
+        for index in 1..bytes.len() {
+            let mut mutated = bytes.clone();
+            mutated[index] ^= 0x01;
+            assert_eq!(
+                signer.verify(&encode(&mutated)),
+                Err(VerifyError::InvalidSignature),
+                "flipping byte {} should invalidate the tag",
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_key_is_rejected() {
+        let id = SignedIdGenerator::new(b"key").generate();
+        assert_eq!(
+            SignedIdGenerator::new(b"other").verify(id.as_str()),
+            Err(VerifyError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old = SignedIdGenerator::new(b"old");
+        let old_id = old.generate();
+
+        let rotated = SignedIdGenerator::new(b"old").with_signing_key(1, b"new");
+        assert_eq!(rotated.signing_key_id(), 1);
+        let new_id = rotated.generate();
+        assert_eq!(new_id.to_bytes().unwrap()[0], 1);
+
+        assert!(rotated.verify(old_id.as_str()).is_ok());
+        assert!(rotated.verify(new_id.as_str()).is_ok());
+        assert_eq!(old.verify(new_id.as_str()), Err(VerifyError::UnknownKey(1)));
+    }
+
+    #[test]
+    fn test_with_key_replaces_existing_key_id() {
+        let signer = SignedIdGenerator::new(b"first").with_key(0, b"second");
+        let id = SignedIdGenerator::new(b"second").generate();
+        assert!(signer.verify(id.as_str()).is_ok());
+        assert_eq!(signer.keys.len(), 1);
+    }
+
+    #[test]
+    fn test_custom_tag_length() {
+        let signer = SignedIdGenerator::new(b"key").with_tag_bytes(16);
+        let id = signer.generate();
+        assert_eq!(id.to_bytes().unwrap().len(), 1 + PAYLOAD_BYTES + 16);
+        assert!(signer.verify(id.as_str()).is_ok());
+        assert!(SignedIdGenerator::new(b"key").verify(id.as_str()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_ids() {
+        let signer = SignedIdGenerator::new(b"key");
+        assert_eq!(
+            signer.verify("not valid!"),
+            Err(VerifyError::Decode(DecodeError::InvalidEncoding))
+        );
+        assert_eq!(
+            signer.verify(&encode(&[0u8; 9])),
+            Err(VerifyError::Decode(DecodeError::InvalidLength(9)))
+        );
+    }
+
+    #[test]
+    fn test_empty_payload_cannot_be_signed_or_verified() {
+        let signer = SignedIdGenerator::new(b"key");
+        assert_eq!(
+            signer.sign(&ShortId::from(String::new())),
+            Err(DecodeError::InvalidLength(0))
+        );
+
+        let mut bytes = vec![0u8];
+        let mut mac = signer.key(0).unwrap().clone();
+        mac.update(&bytes);
+        bytes.extend_from_slice(&mac.finalize().into_bytes()[..8]);
+        assert_eq!(
+            signer.verify(&encode(&bytes)),
+            Err(VerifyError::Decode(DecodeError::InvalidLength(9)))
+        );
+    }
+
+    #[test]
+    fn test_debug_hides_keys() {
+        let signer = SignedIdGenerator::new(b"hunter2").with_signing_key(3, b"hunter3");
+        assert_eq!(
+            format!("{:?}", signer),
+            "SignedIdGenerator { key_ids: [0, 3], signing_key: 3, tag_bytes: 8, .. }"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "tag_bytes must be between 4 and 32")]
+    fn test_short_tag_panics() {
+        SignedIdGenerator::new(b"key").with_tag_bytes(3);
+    }
+}
@@ -0,0 +1,649 @@
+//! Snowflake-style ordered IDs with an embedded node ID and sequence counter.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::ordered::{pause, DEFAULT_MAX_WAIT};
+use crate::{
+    Clock, ClockRegression, DecodeError, GenerateError, OrderedLayout, Precision, RegressionPolicy,
+    ShortId, SystemClock, TimestampOutOfRange,
+};
+
+/// Number of bytes in a Snowflake ID.
+const SNOWFLAKE_BYTES: usize = 8;
+
+/// Describes how a Snowflake ID packs its timestamp, node ID and sequence number.
+///
+/// A Snowflake ID is a single big-endian `u64`, laid out from the most significant bit as:
+///
+/// ```text
+/// [ timestamp: 64 - node_bits - sequence_bits ][ node: node_bits ][ sequence: sequence_bits ]
+/// ```
+///
+/// The timestamp uses the precision and epoch of an [`OrderedLayout`]. The default layout
+/// uses milliseconds since the Unix epoch in 42 bits (good until the year 2109), 10 bits
+/// of node ID (1,024 nodes) and 12 bits of sequence (4,096 IDs per millisecond per node).
+///
+/// Encoded IDs are always 11 characters long.
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{Precision, SnowflakeLayout};
+/// use std::time::Duration;
+///
+/// // Seconds since 2020-01-01, 16 bits of node ID and 8 bits of sequence
+/// const LAYOUT: SnowflakeLayout = SnowflakeLayout::new()
+///     .with_precision(Precision::Seconds)
+///     .with_epoch(Duration::from_secs(1_577_836_800))
+///     .with_node_bits(16)
+///     .with_sequence_bits(8);
+///
+/// assert_eq!(LAYOUT.timestamp_bits(), 40);
+/// assert_eq!(LAYOUT.max_node(), 65_535);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SnowflakeLayout {
+    time: OrderedLayout,
+    node_bits: u32,
+    sequence_bits: u32,
+}
+
+impl SnowflakeLayout {
+    /// The default layout: 42-bit millisecond timestamp since the Unix epoch, 10-bit node
+    /// ID and 12-bit sequence number.
+    pub const DEFAULT: SnowflakeLayout = SnowflakeLayout {
+        time: OrderedLayout::DEFAULT.with_precision(Precision::Milliseconds),
+        node_bits: 10,
+        sequence_bits: 12,
+    };
+
+    /// Creates the default layout. Equivalent to [`SnowflakeLayout::DEFAULT`].
+    pub const fn new() -> Self {
+        Self::DEFAULT
+    }
+
+    /// Sets the precision of the timestamp.
+    pub const fn with_precision(mut self, precision: Precision) -> Self {
+        self.time = self.time.with_precision(precision);
+        self
+    }
+
+    /// Sets the epoch the timestamp counts from, given as an offset from the Unix epoch.
+    pub const fn with_epoch(mut self, since_unix_epoch: Duration) -> Self {
+        self.time = self.time.with_epoch(since_unix_epoch);
+        self
+    }
+
+    /// Sets the number of bits used by the node ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if node and sequence bits together exceed 32.
+    pub const fn with_node_bits(mut self, node_bits: u32) -> Self {
+        assert!(
+            node_bits + self.sequence_bits <= 32,
+            "node_bits + sequence_bits must not exceed 32"
+        );
+        self.node_bits = node_bits;
+        self
+    }
+
+    /// Sets the number of bits used by the per-node sequence number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if node and sequence bits together exceed 32.
+    pub const fn with_sequence_bits(mut self, sequence_bits: u32) -> Self {
+        assert!(
+            self.node_bits + sequence_bits <= 32,
+            "node_bits + sequence_bits must not exceed 32"
+        );
+        self.sequence_bits = sequence_bits;
+        self
+    }
+
+    /// Returns the precision of the timestamp.
+    pub const fn precision(&self) -> Precision {
+        self.time.precision()
+    }
+
+    /// Returns the epoch the timestamp counts from.
+    pub fn epoch(&self) -> SystemTime {
+        self.time.epoch()
+    }
+
+    /// Returns the number of bits used by the timestamp.
+    pub const fn timestamp_bits(&self) -> u32 {
+        64 - self.node_bits - self.sequence_bits
+    }
+
+    /// Returns the number of bits used by the node ID.
+    pub const fn node_bits(&self) -> u32 {
+        self.node_bits
+    }
+
+    /// Returns the number of bits used by the sequence number.
+    pub const fn sequence_bits(&self) -> u32 {
+        self.sequence_bits
+    }
+
+    /// Returns the largest node ID this layout can hold.
+    pub const fn max_node(&self) -> u64 {
+        mask(self.node_bits)
+    }
+
+    /// Returns the largest sequence number this layout can hold.
+    pub const fn max_sequence(&self) -> u64 {
+        mask(self.sequence_bits)
+    }
+
+    /// Decodes the timestamp, node ID and sequence number of a Snowflake ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `id` is not a valid ID string, and
+    /// [`DecodeError::InvalidLength`] if it does not decode to exactly 8 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use short_id::{SnowflakeGenerator, SnowflakeLayout};
+    ///
+    /// let generator = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 7);
+    /// let id = generator.generate().unwrap();
+    ///
+    /// let parts = SnowflakeLayout::DEFAULT.decode(id.as_str()).unwrap();
+    /// assert_eq!(parts.node, 7);
+    /// assert_eq!(parts.sequence, 0);
+    /// ```
+    pub fn decode(&self, id: &str) -> Result<SnowflakeParts, DecodeError> {
+        let bytes = crate::decode(id)?;
+        let bytes: [u8; SNOWFLAKE_BYTES] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| DecodeError::InvalidLength(bytes.len()))?;
+        let value = u64::from_be_bytes(bytes);
+
+        Ok(SnowflakeParts {
+            timestamp: self
+                .time
+                .time_of(value >> (self.node_bits + self.sequence_bits)),
+            node: (value >> self.sequence_bits) & self.max_node(),
+            sequence: value & self.max_sequence(),
+        })
+    }
+
+    /// Packs the three components into an ID.
+    fn encode(&self, ticks: u64, node: u64, sequence: u64) -> ShortId {
+        let value = (ticks << (self.node_bits + self.sequence_bits))
+            | (node << self.sequence_bits)
+            | sequence;
+        ShortId::from_bytes(&value.to_be_bytes())
+    }
+
+    /// Converts `time` into a tick count, checked against the timestamp bits.
+    fn ticks(&self, time: SystemTime) -> Result<u64, TimestampOutOfRange> {
+        let ticks = self.time.checked_ticks(time)?;
+        if ticks > mask(self.timestamp_bits()) {
+            return Err(TimestampOutOfRange::new(time, false));
+        }
+        Ok(ticks)
+    }
+}
+
+impl Default for SnowflakeLayout {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The components of a decoded Snowflake ID.
+///
+/// Returned by [`SnowflakeLayout::decode()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnowflakeParts {
+    /// When the ID was generated, truncated to the layout's precision.
+    pub timestamp: SystemTime,
+    /// The node that generated the ID.
+    pub node: u64,
+    /// The per-node sequence number within the timestamp tick.
+    pub sequence: u64,
+}
+
+/// Generator for Snowflake-style IDs, unique without relying on randomness.
+///
+/// Each ID combines the current timestamp, a node ID that must be unique across the
+/// cluster, and a sequence number that counts IDs generated by this node within the same
+/// timestamp tick. When the sequence is exhausted, the generator waits for the next tick.
+///
+/// If the system clock moves backwards, the generator applies its [`RegressionPolicy`]
+/// (an error by default). With [`RegressionPolicy::Increment`], it keeps the last
+/// timestamp and continues counting the sequence, moving to the next tick on its own if
+/// the sequence runs out before the clock catches up.
+///
+/// The generator keeps its state behind a mutex, so a single instance can be shared
+/// between threads (e.g. behind an `Arc`). The time comes from a [`Clock`], the system
+/// clock unless replaced with [`with_clock()`](Self::with_clock).
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{BinaryId, SnowflakeGenerator, SnowflakeLayout};
+///
+/// let generator = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 42);
+///
+/// let id1 = generator.generate().unwrap();
+/// let id2 = generator.generate().unwrap();
+/// assert_eq!(id1.as_str().len(), 11);
+/// assert!(BinaryId::from(id1) < BinaryId::from(id2));
+/// ```
+#[derive(Debug)]
+pub struct SnowflakeGenerator<C = SystemClock> {
+    layout: SnowflakeLayout,
+    node: u64,
+    policy: RegressionPolicy,
+    max_wait: Duration,
+    clock: C,
+    state: Mutex<SnowflakeState>,
+    regressions: AtomicU64,
+}
+
+/// The last timestamp tick and sequence number a generator handed out.
+#[derive(Debug, Default)]
+struct SnowflakeState {
+    last_tick: u64,
+    sequence: u64,
+}
+
+impl SnowflakeGenerator {
+    /// Creates a generator for the given node.
+    ///
+    /// The generator starts with the [`RegressionPolicy::Error`] policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` does not fit in the layout's node bits.
+    pub fn new(layout: SnowflakeLayout, node: u64) -> Self {
+        assert!(
+            node <= layout.max_node(),
+            "node must not exceed {} (got {})",
+            layout.max_node(),
+            node
+        );
+        SnowflakeGenerator {
+            layout,
+            node,
+            policy: RegressionPolicy::default(),
+            max_wait: DEFAULT_MAX_WAIT,
+            clock: SystemClock,
+            state: Mutex::new(SnowflakeState::default()),
+            regressions: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<C: Clock> SnowflakeGenerator<C> {
+    /// Replaces the clock that timestamps the generated IDs.
+    ///
+    /// Once the sequence runs out, the generator waits for this clock to reach the next
+    /// tick, so a clock that never advances blocks it.
+    pub fn with_clock<D: Clock>(self, clock: D) -> SnowflakeGenerator<D> {
+        SnowflakeGenerator {
+            layout: self.layout,
+            node: self.node,
+            policy: self.policy,
+            max_wait: self.max_wait,
+            clock,
+            state: self.state,
+            regressions: self.regressions,
+        }
+    }
+
+    /// Sets the policy applied when the clock moves backwards.
+    pub fn with_policy(mut self, policy: RegressionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets how long [`RegressionPolicy::Wait`] waits for the clock before returning a
+    /// [`ClockRegression`] error (one second by default).
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Returns the layout of the generated IDs.
+    pub fn layout(&self) -> &SnowflakeLayout {
+        &self.layout
+    }
+
+    /// Returns the node ID embedded in the generated IDs.
+    pub fn node(&self) -> u64 {
+        self.node
+    }
+
+    /// Returns the policy applied when the clock moves backwards.
+    pub fn policy(&self) -> RegressionPolicy {
+        self.policy
+    }
+
+    /// Returns how long [`RegressionPolicy::Wait`] waits for the clock.
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// Returns the clock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns how many times [`generate()`](Self::generate) found the clock behind the
+    /// last generated ID, regardless of the policy applied.
+    pub fn regressions(&self) -> u64 {
+        self.regressions.load(Ordering::Relaxed)
+    }
+
+    /// Generates the next ID.
+    ///
+    /// IDs generated by the same generator are strictly increasing. The generator does not
+    /// hold its lock while waiting for the clock, whether for the next tick once the
+    /// sequence is exhausted or under [`RegressionPolicy::Wait`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GenerateError::ClockRegression`] if the clock is earlier than the
+    /// timestamp of the previously generated ID and the policy is
+    /// [`RegressionPolicy::Error`] (or [`RegressionPolicy::Wait`] and the clock did not
+    /// catch up within the maximum wait), and [`GenerateError::OutOfRange`] if the clock is
+    /// before the layout's epoch, the current time no longer fits in the timestamp bits, or
+    /// [`RegressionPolicy::Increment`] needs to move past the last timestamp.
+    pub fn generate(&self) -> Result<ShortId, GenerateError> {
+        let mut deadline = None;
+        loop {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let time = self.clock.now();
+            let mut now = self.layout.ticks(time)?;
+            let behind = now < state.last_tick;
+
+            if behind {
+                // Count each call once, not every time a wait wakes up
+                if deadline.is_none() {
+                    self.regressions.fetch_add(1, Ordering::Relaxed);
+                }
+                let regression = ClockRegression::new(
+                    self.layout.time.time_of(state.last_tick),
+                    self.layout.time.time_of(now),
+                );
+                match self.policy {
+                    RegressionPolicy::Error => return Err(regression.into()),
+                    RegressionPolicy::Wait => {
+                        drop(state);
+                        let deadline =
+                            *deadline.get_or_insert_with(|| Instant::now() + self.max_wait);
+                        if !pause(&self.clock, regression.last(), Some(deadline)) {
+                            return Err(regression.into());
+                        }
+                        continue;
+                    }
+                    RegressionPolicy::Increment => now = state.last_tick,
+                }
+            }
+
+            if now == state.last_tick {
+                if state.sequence < self.layout.max_sequence() {
+                    state.sequence += 1;
+                } else if behind {
+                    // The clock is behind, so waiting for it would stall: move on by itself
+                    if now == mask(self.layout.timestamp_bits()) {
+                        return Err(TimestampOutOfRange::new(time, false).into());
+                    }
+                    now += 1;
+                    state.sequence = 0;
+                } else {
+                    let next = self.layout.time.time_of(state.last_tick + 1);
+                    drop(state);
+                    pause(&self.clock, next, None);
+                    continue;
+                }
+            } else {
+                state.sequence = 0;
+            }
+            state.last_tick = now;
+
+            return Ok(self.layout.encode(now, self.node, state.sequence));
+        }
+    }
+}
+
+/// Returns a mask of the lowest `bits` bits.
+const fn mask(bits: u32) -> u64 {
+    if bits == 0 {
+        0
+    } else {
+        u64::MAX >> (64 - bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::UNIX_EPOCH;
+
+    use crate::BinaryId;
+
+    fn at_millis(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    /// Returns a clock reading the time set through the returned cell.
+    fn manual_clock(start: SystemTime) -> (Rc<Cell<SystemTime>>, impl Fn() -> SystemTime) {
+        let now = Rc::new(Cell::new(start));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || now.get()
+        };
+        (now, clock)
+    }
+
+    #[test]
+    fn test_default_layout_bits() {
+        let layout = SnowflakeLayout::DEFAULT;
+        assert_eq!(layout.timestamp_bits(), 42);
+        assert_eq!(layout.max_node(), 1023);
+        assert_eq!(layout.max_sequence(), 4095);
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let layout = SnowflakeLayout::new()
+            .with_node_bits(5)
+            .with_sequence_bits(3);
+        let id = layout.encode(123_456, 31, 5);
+        assert_eq!(id.as_str().len(), 11);
+
+        let parts = layout.decode(id.as_str()).unwrap();
+        assert_eq!(parts.timestamp, layout.time.time_of(123_456));
+        assert_eq!(parts.node, 31);
+        assert_eq!(parts.sequence, 5);
+    }
+
+    #[test]
+    fn test_generate_embeds_node_and_time() {
+        let generator = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 513);
+        let before = SystemTime::now();
+        let id = generator.generate().unwrap();
+
+        let parts = generator.layout().decode(id.as_str()).unwrap();
+        assert_eq!(parts.node, 513);
+        assert!(
+            before.duration_since(parts.timestamp).unwrap_or_default() < Duration::from_millis(2)
+        );
+    }
+
+    #[test]
+    fn test_ids_are_strictly_increasing() {
+        let generator = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 1);
+        let ids: Vec<BinaryId> = (0..10_000)
+            .map(|_| BinaryId::from(generator.generate().unwrap()))
+            .collect();
+        for pair in ids.windows(2) {
+            assert!(
+                pair[0] < pair[1],
+                "{} should sort before {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_sequence_exhaustion_waits_for_next_tick() {
+        let layout = SnowflakeLayout::new().with_sequence_bits(1);
+        let generator = SnowflakeGenerator::new(layout, 0);
+        let parts: Vec<SnowflakeParts> = (0..6)
+            .map(|_| {
+                layout
+                    .decode(generator.generate().unwrap().as_str())
+                    .unwrap()
+            })
+            .collect();
+        for pair in parts.windows(2) {
+            if pair[0].timestamp == pair[1].timestamp {
+                assert_eq!(pair[1].sequence, pair[0].sequence + 1);
+            } else {
+                assert!(pair[0].timestamp < pair[1].timestamp);
+                assert_eq!(pair[1].sequence, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clock_regression_is_detected() {
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 0).with_clock(clock);
+        generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_000));
+        let Err(GenerateError::ClockRegression(err)) = generator.generate() else {
+            panic!("expected a clock regression");
+        };
+        assert_eq!(err.last(), at_millis(1_700_000_000_000));
+        assert_eq!(err.drift(), Duration::from_secs(1));
+        assert_eq!(generator.regressions(), 1);
+    }
+
+    #[test]
+    fn test_increment_policy_keeps_counting() {
+        let layout = SnowflakeLayout::new().with_sequence_bits(1);
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = SnowflakeGenerator::new(layout, 3)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Increment);
+        generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_000));
+        let parts: Vec<SnowflakeParts> = (0..3)
+            .map(|_| {
+                layout
+                    .decode(generator.generate().unwrap().as_str())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(parts[0].timestamp, at_millis(1_700_000_000_000));
+        assert_eq!(parts[0].sequence, 1);
+        assert_eq!(parts[1].timestamp, at_millis(1_700_000_000_001));
+        assert_eq!(parts[1].sequence, 0);
+        assert_eq!(parts[2].sequence, 1);
+        assert_eq!(generator.regressions(), 3);
+    }
+
+    #[test]
+    fn test_increment_policy_stops_at_last_timestamp() {
+        let layout = SnowflakeLayout::new()
+            .with_precision(Precision::Seconds)
+            .with_sequence_bits(1)
+            .with_node_bits(31);
+        let last = UNIX_EPOCH + Duration::from_secs(mask(layout.timestamp_bits()));
+        let (now, clock) = manual_clock(last);
+        let generator = SnowflakeGenerator::new(layout, 3)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Increment);
+        generator.generate().unwrap();
+
+        now.set(last - Duration::from_secs(1));
+        generator.generate().unwrap();
+        let Err(GenerateError::OutOfRange(err)) = generator.generate() else {
+            panic!("expected the timestamp to be out of range");
+        };
+        assert!(!err.is_before_epoch());
+        assert_eq!(err.time(), last - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_wait_policy_waits_for_clock() {
+        // Each reading advances the clock by a millisecond
+        let now = Rc::new(Cell::new(at_millis(1_700_000_000_000)));
+        let clock = {
+            let now = Rc::clone(&now);
+            move || {
+                let time = now.get();
+                now.set(time + Duration::from_millis(1));
+                time
+            }
+        };
+        let generator = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 3)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Wait);
+        generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_995));
+        let id = generator.generate().unwrap();
+        let parts = generator.layout.decode(id.as_str()).unwrap();
+        assert!(parts.timestamp >= at_millis(1_700_000_000_000));
+        assert_eq!(generator.regressions(), 1);
+    }
+
+    #[test]
+    fn test_wait_policy_gives_up() {
+        let (now, clock) = manual_clock(at_millis(1_700_000_000_000));
+        let generator = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 3)
+            .with_clock(clock)
+            .with_policy(RegressionPolicy::Wait)
+            .with_max_wait(Duration::from_millis(20));
+        generator.generate().unwrap();
+
+        now.set(at_millis(1_699_999_999_000));
+        let started = Instant::now();
+        let Err(GenerateError::ClockRegression(err)) = generator.generate() else {
+            panic!("expected a clock regression");
+        };
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(err.drift(), Duration::from_secs(1));
+        assert_eq!(generator.regressions(), 1);
+    }
+
+    #[test]
+    fn test_decode_rejects_wrong_length() {
+        let id = crate::short_id();
+        assert_eq!(
+            SnowflakeLayout::DEFAULT.decode(&id),
+            Err(DecodeError::InvalidLength(10))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "node must not exceed 1023 (got 1024)")]
+    fn test_node_out_of_range_panics() {
+        SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 1024);
+    }
+
+    #[test]
+    #[should_panic(expected = "node_bits + sequence_bits must not exceed 32")]
+    fn test_too_many_bits_panics() {
+        SnowflakeLayout::new().with_node_bits(21);
+    }
+}
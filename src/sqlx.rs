@@ -0,0 +1,203 @@
+//! `sqlx` support for [`ShortId`] and [`BinaryId`].
+//!
+//! The impls are generic over the database, delegating to `String` for text columns and
+//! `Vec<u8>` for binary columns, so they cover every driver `sqlx` ships (SQLite,
+//! Postgres and MySQL) without this crate enabling any of them.
+
+use ::sqlx::{encode::IsNull, error::BoxDynError, Database, Decode, Encode, Type};
+
+use crate::{BinaryId, ShortId};
+
+impl<DB: Database> Type<DB> for ShortId
+where
+    String: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <String as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <String as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for ShortId
+where
+    String: Encode<'q, DB>,
+{
+    fn encode(self, buf: &mut DB::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        self.0.encode(buf)
+    }
+
+    fn encode_by_ref(&self, buf: &mut DB::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        self.0.encode_by_ref(buf)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.size_hint()
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for ShortId
+where
+    String: Decode<'r, DB>,
+{
+    /// Decodes a text value, rejecting strings that are not a valid ID encoding.
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let id = String::decode(value)?;
+        ShortId::parse(&id)?;
+        Ok(ShortId(id))
+    }
+}
+
+impl<DB: Database> Type<DB> for BinaryId
+where
+    Vec<u8>: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <Vec<u8> as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <Vec<u8> as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for BinaryId
+where
+    Vec<u8>: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut DB::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        self.to_bytes()?.encode(buf)
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for BinaryId
+where
+    Vec<u8>: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(BinaryId::from_bytes(&Vec::<u8>::decode(value)?))
+    }
+}
+
+#[cfg(all(test, feature = "sqlx-sqlite"))]
+mod tests {
+    use super::*;
+
+    use ::sqlx::{Connection, Row, Sqlite, SqliteConnection};
+
+    async fn connect() -> SqliteConnection {
+        SqliteConnection::connect("sqlite::memory:").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_text_roundtrip() {
+        let mut conn = connect().await;
+        let id = ShortId::random();
+
+        let row = ::sqlx::query("SELECT ? AS id, typeof(?) AS kind")
+            .bind(&id)
+            .bind(&id)
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(row.get::<ShortId, _>("id"), id);
+        assert_eq!(row.get::<String, _>("kind"), "text");
+    }
+
+    #[tokio::test]
+    async fn test_binary_roundtrip() {
+        let mut conn = connect().await;
+        let id = BinaryId(ShortId::random());
+
+        let row = ::sqlx::query("SELECT ? AS id, length(?) AS len")
+            .bind(&id)
+            .bind(&id)
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(row.get::<BinaryId, _>("id"), id);
+        assert_eq!(row.get::<i64, _>("len"), 10);
+    }
+
+    #[tokio::test]
+    async fn test_decode_rejects_invalid_ids() {
+        let mut conn = connect().await;
+
+        let result: Result<ShortId, _> = ::sqlx::query_scalar("SELECT 'not valid!'")
+            .fetch_one(&mut conn)
+            .await;
+        assert!(matches!(result, Err(::sqlx::Error::ColumnDecode { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_encode_rejects_invalid_binary_ids() {
+        let mut conn = connect().await;
+        let id = BinaryId(ShortId::from(String::from("not valid!")));
+
+        let result = ::sqlx::query("SELECT ?")
+            .bind(&id)
+            .fetch_one(&mut conn)
+            .await;
+        assert!(matches!(result, Err(::sqlx::Error::Encode(_))));
+    }
+
+    #[tokio::test]
+    async fn test_binary_column_keeps_ordered_ids_in_order() {
+        let mut conn = connect().await;
+        ::sqlx::query("CREATE TABLE events (id BLOB PRIMARY KEY)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let mut ids = Vec::new();
+        for _ in 0..20 {
+            let id = ShortId::ordered();
+            ::sqlx::query("INSERT INTO events (id) VALUES (?)")
+                .bind(BinaryId(id.clone()))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+            ids.push(id);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        let stored: Vec<BinaryId> = ::sqlx::query_scalar("SELECT id FROM events ORDER BY id DESC")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+        ids.reverse();
+        assert_eq!(
+            stored.into_iter().map(ShortId::from).collect::<Vec<_>>(),
+            ids
+        );
+    }
+
+    #[test]
+    fn test_column_types() {
+        assert_eq!(
+            <ShortId as Type<Sqlite>>::type_info(),
+            <String as Type<Sqlite>>::type_info()
+        );
+        assert_eq!(
+            <BinaryId as Type<Sqlite>>::type_info(),
+            <Vec<u8> as Type<Sqlite>>::type_info()
+        );
+    }
+
+    #[cfg(feature = "sqlx-postgres")]
+    #[test]
+    fn test_postgres_column_types() {
+        use ::sqlx::Postgres;
+
+        assert_eq!(
+            <ShortId as Type<Postgres>>::type_info(),
+            <String as Type<Postgres>>::type_info()
+        );
+        assert_eq!(
+            <BinaryId as Type<Postgres>>::type_info(),
+            <Vec<u8> as Type<Postgres>>::type_info()
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Hashed storage of secret tokens.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::{decode, encode, DecodeError};
+
+/// SHA-256 digest of a secret token, for storing in place of the token.
+///
+/// If a database holds session or API tokens verbatim, anyone who reads it can use
+/// them. Storing the digest instead means a leak only reveals values that cannot be
+/// presented back. A single fast hash is enough here (no salt or key stretching) because
+/// tokens from this crate are random with at least 80 bits of entropy, unlike passwords.
+///
+/// The digest displays as a 43-character string in the ID alphabet, and converts to and
+/// from its 32 raw bytes.
+///
+/// **This type requires the `secret` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{short_id_with_bytes, TokenDigest};
+///
+/// let token = short_id_with_bytes(24);
+/// let digest = TokenDigest::of(&token);   // store this
+///
+/// assert!(digest.verify(&token));
+/// assert!(!digest.verify(&short_id_with_bytes(24)));
+///
+/// let stored = digest.to_string();
+/// assert_eq!(stored.len(), 43);
+/// assert_eq!(TokenDigest::parse(&stored), Ok(digest));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct TokenDigest([u8; 32]);
+
+impl TokenDigest {
+    /// Computes the digest of `token`.
+    pub fn of(token: &str) -> Self {
+        TokenDigest(Sha256::digest(token.as_bytes()).into())
+    }
+
+    /// Wraps a digest loaded from storage as raw bytes.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        TokenDigest(bytes)
+    }
+
+    /// Returns the raw digest bytes.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Parses a digest stored in its string form (see `Display`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecodeError::InvalidEncoding`] if `digest` is not a valid encoding, and
+    /// [`DecodeError::InvalidLength`] if it does not decode to 32 bytes.
+    pub fn parse(digest: &str) -> Result<Self, DecodeError> {
+        let bytes = decode(digest)?;
+        let bytes: [u8; 32] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| DecodeError::InvalidLength(bytes.len()))?;
+        Ok(TokenDigest(bytes))
+    }
+
+    /// Returns `true` if `token` hashes to this digest.
+    ///
+    /// The digests are compared in constant time, so the running time reveals nothing
+    /// about how close a guess was.
+    pub fn verify(&self, token: &str) -> bool {
+        TokenDigest::of(token).0.ct_eq(&self.0).into()
+    }
+}
+
+impl PartialEq for TokenDigest {
+    /// Compares in constant time, like [`verify()`](Self::verify).
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl Eq for TokenDigest {}
+
+impl core::hash::Hash for TokenDigest {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl core::fmt::Display for TokenDigest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&encode(&self.0))
+    }
+}
+
+/// The storable form of a secret token: a plain lookup prefix and a digest.
+///
+/// A digest alone can only be found by hashing the presented token and looking the
+/// digest up, which requires an index on the digest. The lookup-prefix scheme stores the
+/// first few characters of the token in plain text instead, so a presented token is
+/// found by an ordinary index on the prefix and then confirmed with the digest:
+///
+/// 1. When issuing a token, store [`lookup()`](Self::lookup) and
+///    [`digest()`](Self::digest), never the token itself.
+/// 2. When a token is presented, query by [`StoredToken::lookup_key()`], then call
+///    [`verify()`](Self::verify) on the row.
+///
+/// The stored prefix is visible to anyone who reads the database, so it no longer counts
+/// towards the token's secrecy: a 24-byte (32-character) token with an 8-character
+/// prefix keeps 24 secret characters, or 144 bits.
+///
+/// **This type requires the `secret` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use short_id::{short_id_with_bytes, StoredToken};
+///
+/// // Issue: hand out `token`, persist `stored`
+/// let token = short_id_with_bytes(24);
+/// let stored = StoredToken::new(&token, 8);
+///
+/// // Present: look the row up by prefix, then verify
+/// let key = StoredToken::lookup_key(&token, 8).unwrap();
+/// assert_eq!(key, stored.lookup());
+/// assert!(stored.verify(&token));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StoredToken {
+    lookup: String,
+    digest: TokenDigest,
+}
+
+impl StoredToken {
+    /// Splits `token` into its first `lookup_len` characters and the digest of the whole
+    /// token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lookup_len` is not less than the length of `token`, or does not fall on
+    /// a character boundary.
+    pub fn new(token: &str, lookup_len: usize) -> Self {
+        let lookup = StoredToken::lookup_key(token, lookup_len)
+            .expect("lookup_len must be less than the token length");
+        StoredToken {
+            lookup: String::from(lookup),
+            digest: TokenDigest::of(token),
+        }
+    }
+
+    /// Rebuilds a stored token from the columns it was persisted in.
+    pub fn from_parts(lookup: String, digest: TokenDigest) -> Self {
+        StoredToken { lookup, digest }
+    }
+
+    /// Returns the first `lookup_len` characters of a presented token, to query by.
+    ///
+    /// Returns `None` if the token is too short to have a secret part beyond the prefix,
+    /// so malformed input can be rejected without a query.
+    pub fn lookup_key(token: &str, lookup_len: usize) -> Option<&str> {
+        if token.len() <= lookup_len {
+            return None;
+        }
+        token.get(..lookup_len)
+    }
+
+    /// Returns the plain lookup prefix.
+    pub fn lookup(&self) -> &str {
+        &self.lookup
+    }
+
+    /// Returns the digest of the whole token.
+    pub fn digest(&self) -> &TokenDigest {
+        &self.digest
+    }
+
+    /// Returns `true` if `token` is the token this was created from.
+    ///
+    /// The digest is compared in constant time; the prefix is not secret and is compared
+    /// normally.
+    pub fn verify(&self, token: &str) -> bool {
+        let prefix_matches = token.len() > self.lookup.len()
+            && token.as_bytes()[..self.lookup.len()] == *self.lookup.as_bytes();
+        // Always hash, so the running time doesn't depend on the prefix comparison
+        let digest_matches = self.digest.verify(token);
+        prefix_matches & digest_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_digest_is_sha256() {
+        let digest = TokenDigest::of("abc");
+        assert_eq!(
+            digest.as_bytes()[..4],
+            [0xba, 0x78, 0x16, 0xbf],
+            "SHA-256(\"abc\") starts with ba7816bf"
+        );
+        assert_eq!(TokenDigest::from_bytes(*digest.as_bytes()), digest);
+    }
+
+    #[test]
+    fn test_verify() {
+        let token = crate::short_id_with_bytes(24);
+        let digest = TokenDigest::of(&token);
+        assert!(digest.verify(&token));
+        assert!(!digest.verify(&token[1..]));
+        assert!(!digest.verify(""));
+    }
+
+    #[test]
+    fn test_digest_equality() {
+        let digest = TokenDigest::of("token");
+        assert_eq!(digest, TokenDigest::of("token"));
+        assert_ne!(digest, TokenDigest::of("tokem"));
+
+        let mut bytes = *digest.as_bytes();
+        bytes[31] ^= 1;
+        assert_ne!(digest, TokenDigest::from_bytes(bytes));
+    }
+
+    #[test]
+    fn test_digest_string_roundtrip() {
+        let digest = TokenDigest::of("token");
+        let encoded = digest.to_string();
+        assert_eq!(encoded.len(), 43);
+        assert_eq!(TokenDigest::parse(&encoded), Ok(digest));
+        assert_eq!(
+            TokenDigest::parse(&crate::short_id()),
+            Err(DecodeError::InvalidLength(10))
+        );
+    }
+
+    #[test]
+    fn test_stored_token() {
+        let token = crate::short_id_with_bytes(24);
+        let stored = StoredToken::new(&token, 8);
+        assert_eq!(stored.lookup(), &token[..8]);
+        assert_eq!(stored.digest(), &TokenDigest::of(&token));
+        assert!(stored.verify(&token));
+
+        let rebuilt = StoredToken::from_parts(stored.lookup().to_string(), *stored.digest());
+        assert_eq!(rebuilt, stored);
+    }
+
+    #[test]
+    fn test_stored_token_rejects_other_tokens() {
+        let token = crate::short_id_with_bytes(24);
+        let stored = StoredToken::new(&token, 8);
+
+        // Same prefix, different secret part
+        let mut forged = String::from(&token[..8]);
+        forged.push_str(&crate::short_id_with_bytes(24)[8..]);
+        assert!(!stored.verify(&forged));
+
+        // The prefix alone is not enough
+        assert!(!stored.verify(&token[..8]));
+    }
+
+    #[test]
+    fn test_lookup_key() {
+        assert_eq!(StoredToken::lookup_key("abcdefghij", 4), Some("abcd"));
+        assert_eq!(StoredToken::lookup_key("abcd", 4), None);
+        assert_eq!(StoredToken::lookup_key("", 0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "lookup_len must be less than the token length")]
+    fn test_lookup_len_too_long_panics() {
+        StoredToken::new("short", 5);
+    }
+}
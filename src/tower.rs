@@ -0,0 +1,391 @@
+//! `tower` middleware for request IDs.
+//!
+//! [`RequestIdLayer`] gives every request a [`ShortId`]: the one the client (or a proxy in
+//! front of the service) sent in the `x-request-id` header if it is a valid ID, or a new
+//! [`short_id_ordered()`](crate::short_id_ordered) otherwise. Ordered IDs sort by the time
+//! the request arrived, which keeps logs and traces in order when searched by ID.
+//!
+//! The ID is inserted into the request's extensions, where handlers and inner middleware
+//! can read it (e.g. with `axum::Extension<ShortId>`), and into the response's headers.
+//!
+//! For services that already use `tower-http`'s `SetRequestIdLayer`, [`MakeShortRequestId`]
+//! implements its `MakeRequestId` trait instead.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use http::{HeaderName, HeaderValue, Request, Response};
+use pin_project_lite::pin_project;
+use tower_http::request_id::{MakeRequestId, RequestId};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{ShortId, MAX_BYTES};
+
+/// The header request IDs are read from and written to by default.
+const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The longest ID accepted from a request by default: the length of an ordered ID.
+const DEFAULT_MAX_BYTES: usize = 10;
+
+/// Returns the ID in `value` if it is a valid ID of at most `max_bytes` bytes.
+fn parse_header(value: &HeaderValue, max_bytes: usize) -> Option<ShortId> {
+    let value = value.to_str().ok()?;
+    // An empty header counts as missing; check the length first, so an oversized header
+    // is rejected without decoding it
+    if value.is_empty() || value.len() > (max_bytes * 4).div_ceil(3) {
+        return None;
+    }
+    ShortId::parse(value).ok()
+}
+
+/// Returns the header value for an ID.
+fn header_value(id: &ShortId) -> HeaderValue {
+    // The ID alphabet is a subset of the characters allowed in header values
+    HeaderValue::from_str(id.as_str()).expect("IDs are valid header values")
+}
+
+/// A [`Layer`] that gives every request a [`ShortId`] request ID.
+///
+/// A valid ID in the request's `x-request-id` header is kept; a missing or invalid one is
+/// replaced with a new ordered ID, so a client can't inject arbitrary text into logs. So
+/// is an ID longer than an ordered ID (10 bytes, 14 characters), unless
+/// [`with_max_bytes()`](Self::with_max_bytes) allows longer ones. The ID is then:
+///
+/// - set as the request's `x-request-id` header
+/// - inserted into the request's extensions as a [`ShortId`]
+/// - set as the response's `x-request-id` header, unless the inner service set one
+///
+/// **This type requires the `tower` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use http::{Request, Response};
+/// use short_id::{RequestIdLayer, ShortId};
+/// use tower::{service_fn, ServiceBuilder, ServiceExt};
+///
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let service = ServiceBuilder::new()
+///     .layer(RequestIdLayer::new())
+///     .service(service_fn(|request: Request<()>| async move {
+///         let id = request.extensions().get::<ShortId>().unwrap();
+///         Ok::<_, std::convert::Infallible>(Response::new(format!("handled {}", id)))
+///     }));
+///
+/// let response = service.oneshot(Request::new(())).await.unwrap();
+/// let id = response.headers()["x-request-id"].to_str().unwrap();
+/// assert!(ShortId::parse(id).is_ok());
+/// # });
+/// ```
+#[derive(Clone, Debug)]
+pub struct RequestIdLayer {
+    header: HeaderName,
+    max_bytes: usize,
+}
+
+impl RequestIdLayer {
+    /// Creates a layer using the `x-request-id` header, accepting IDs of up to 10 bytes.
+    pub fn new() -> Self {
+        Self {
+            header: X_REQUEST_ID,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Sets the header request IDs are read from and written to.
+    pub fn with_header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Sets the number of bytes of the longest ID kept from a request; longer ones are
+    /// replaced like invalid ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_bytes` is 0 or exceeds 32.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        assert!(max_bytes > 0, "max_bytes must be greater than 0");
+        assert!(
+            max_bytes <= MAX_BYTES,
+            "max_bytes must not exceed {} (got {})",
+            MAX_BYTES,
+            max_bytes
+        );
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+impl Default for RequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService {
+            inner,
+            header: self.header.clone(),
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+/// The service created by [`RequestIdLayer`].
+///
+/// **This type requires the `tower` feature.**
+#[derive(Clone, Debug)]
+pub struct RequestIdService<S> {
+    inner: S,
+    header: HeaderName,
+    max_bytes: usize,
+}
+
+impl<S> RequestIdService<S> {
+    /// Wraps `inner`, using the `x-request-id` header.
+    pub fn new(inner: S) -> Self {
+        RequestIdLayer::new().layer(inner)
+    }
+
+    /// Returns the wrapped service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = RequestIdFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let received = request.headers().get(&self.header);
+        let id = match received.and_then(|value| parse_header(value, self.max_bytes)) {
+            Some(id) => id,
+            None => {
+                let id = ShortId::ordered();
+                request
+                    .headers_mut()
+                    .insert(self.header.clone(), header_value(&id));
+                id
+            }
+        };
+        let value = header_value(&id);
+        request.extensions_mut().insert(id);
+
+        RequestIdFuture {
+            inner: self.inner.call(request),
+            header: Some((self.header.clone(), value)),
+        }
+    }
+}
+
+pin_project! {
+    /// The response future of [`RequestIdService`].
+    ///
+    /// **This type requires the `tower` feature.**
+    pub struct RequestIdFuture<F> {
+        #[pin]
+        inner: F,
+        header: Option<(HeaderName, HeaderValue)>,
+    }
+}
+
+impl<F, ResBody, E> Future for RequestIdFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<ResBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = core::task::ready!(this.inner.poll(cx))?;
+        if let Some((name, value)) = this.header.take() {
+            response.headers_mut().entry(name).or_insert(value);
+        }
+        Poll::Ready(Ok(response))
+    }
+}
+
+/// Mints ordered [`ShortId`]s for `tower-http`'s request ID middleware.
+///
+/// Use it with `SetRequestIdLayer` and `PropagateRequestIdLayer` when a service already
+/// uses them, e.g. for their integration with `TraceLayer`. Unlike [`RequestIdLayer`],
+/// `SetRequestIdLayer` keeps any ID the client sent, valid or not, and inserts a
+/// `tower_http::request_id::RequestId` rather than a [`ShortId`] into the extensions.
+///
+/// **This type requires the `tower` feature.**
+///
+/// # Examples
+///
+/// ```
+/// use http::{Request, Response};
+/// use short_id::{MakeShortRequestId, ShortId};
+/// use tower::{service_fn, ServiceBuilder, ServiceExt};
+/// use tower_http::request_id::{PropagateRequestIdLayer, SetRequestIdLayer};
+///
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let service = ServiceBuilder::new()
+///     .layer(SetRequestIdLayer::x_request_id(MakeShortRequestId))
+///     .layer(PropagateRequestIdLayer::x_request_id())
+///     .service(service_fn(|_: Request<()>| async {
+///         Ok::<_, std::convert::Infallible>(Response::new(()))
+///     }));
+///
+/// let response = service.oneshot(Request::new(())).await.unwrap();
+/// let id = response.headers()["x-request-id"].to_str().unwrap();
+/// assert!(ShortId::parse(id).is_ok());
+/// # });
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MakeShortRequestId;
+
+impl MakeRequestId for MakeShortRequestId {
+    fn make_request_id<B>(&mut self, _: &Request<B>) -> Option<RequestId> {
+        Some(RequestId::new(header_value(&ShortId::ordered())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::convert::Infallible;
+
+    use ::tower::{service_fn, ServiceExt};
+
+    /// Echoes the ID from the request's extensions in the response body.
+    async fn echo(request: Request<()>) -> Result<Response<String>, Infallible> {
+        let id = request.extensions().get::<ShortId>().unwrap();
+        assert_eq!(
+            request.headers()["x-request-id"].to_str().unwrap(),
+            id.as_str()
+        );
+        Ok(Response::new(id.to_string()))
+    }
+
+    async fn send(request: Request<()>) -> Response<String> {
+        RequestIdService::new(service_fn(echo))
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mints_missing_id() {
+        let response = send(Request::new(())).await;
+        let id = response.headers()["x-request-id"].to_str().unwrap();
+        assert_eq!(id, response.body());
+        assert!(ShortId::parse(id).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_keeps_valid_id() {
+        let id = ShortId::ordered();
+        let request = Request::builder()
+            .header("x-request-id", id.as_str())
+            .body(())
+            .unwrap();
+        let response = send(request).await;
+        assert_eq!(response.headers()["x-request-id"], id.as_str());
+        assert_eq!(response.body(), id.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_replaces_invalid_id() {
+        for sent in ["<script>", ""] {
+            let request = Request::builder()
+                .header("x-request-id", sent)
+                .body(())
+                .unwrap();
+            let response = send(request).await;
+            let id = response.headers()["x-request-id"].to_str().unwrap();
+            assert_ne!(id, sent);
+            assert_eq!(id, response.body());
+            assert!(ShortId::parse(id).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replaces_oversized_id() {
+        let ordered = ShortId::ordered();
+        let long = ShortId::from(crate::short_id_with_bytes(11));
+        for (max_bytes, sent, kept) in [
+            (None, &ordered, true),
+            (None, &long, false),
+            (Some(11), &long, true),
+        ] {
+            let mut layer = RequestIdLayer::new();
+            if let Some(max_bytes) = max_bytes {
+                layer = layer.with_max_bytes(max_bytes);
+            }
+            let request = Request::builder()
+                .header("x-request-id", sent.as_str())
+                .body(())
+                .unwrap();
+            let response = layer
+                .layer(service_fn(echo))
+                .oneshot(request)
+                .await
+                .unwrap();
+            assert_eq!(response.body() == sent.as_str(), kept);
+        }
+
+        // Far longer than any ID: rejected by length alone
+        let request = Request::builder()
+            .header("x-request-id", "A".repeat(4096))
+            .body(())
+            .unwrap();
+        let response = send(request).await;
+        assert_eq!(response.body().len(), 14);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_bytes must not exceed 32 (got 33)")]
+    fn test_max_bytes_out_of_range_panics() {
+        RequestIdLayer::new().with_max_bytes(33);
+    }
+
+    #[tokio::test]
+    async fn test_custom_header_and_existing_response_header() {
+        let service = RequestIdLayer::new()
+            .with_header(HeaderName::from_static("x-trace-id"))
+            .layer(service_fn(|request: Request<()>| async move {
+                assert!(request.headers().contains_key("x-trace-id"));
+                assert!(!request.headers().contains_key("x-request-id"));
+                let mut response = Response::new(());
+                response
+                    .headers_mut()
+                    .insert("x-trace-id", HeaderValue::from_static("upstream"));
+                Ok::<_, Infallible>(response)
+            }));
+        let response = service.oneshot(Request::new(())).await.unwrap();
+        assert_eq!(response.headers()["x-trace-id"], "upstream");
+    }
+
+    #[test]
+    fn test_make_request_id() {
+        let id = MakeShortRequestId
+            .make_request_id(&Request::new(()))
+            .unwrap();
+        let id = id.header_value().to_str().unwrap();
+        assert!(ShortId::parse(id).is_ok());
+    }
+}
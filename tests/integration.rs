@@ -161,3 +161,829 @@ fn test_short_id_into_string() {
     let s: String = id.clone().into();
     assert_eq!(s, id.as_str());
 }
+
+#[test]
+fn test_short_id_bytes_roundtrip() {
+    let id = ShortId::random();
+    let bytes = id.to_bytes().expect("generated IDs should decode");
+    assert_eq!(bytes.len(), 10, "default IDs should decode to 10 bytes");
+    assert_eq!(
+        ShortId::from_bytes(&bytes),
+        id,
+        "from_bytes should invert to_bytes"
+    );
+}
+
+#[test]
+fn test_binary_id_order_matches_byte_order() {
+    use short_id::BinaryId;
+
+    let mut ids: Vec<BinaryId> = (0..100)
+        .map(|_| BinaryId::from(ShortId::random()))
+        .collect();
+    ids.sort();
+    let bytes: Vec<Vec<u8>> = ids.iter().map(|id| id.to_bytes().unwrap()).collect();
+    let mut sorted_bytes = bytes.clone();
+    sorted_bytes.sort();
+    assert_eq!(
+        bytes, sorted_bytes,
+        "BinaryId order should match byte order"
+    );
+}
+
+// Tests for OrderedLayout
+#[cfg(feature = "std")]
+#[test]
+fn test_ordered_layout_timestamp_decodes_short_id_ordered() {
+    use short_id::OrderedLayout;
+
+    let before = std::time::SystemTime::now();
+    let id = short_id_ordered();
+    let created = OrderedLayout::DEFAULT
+        .timestamp(&id)
+        .expect("default layout should decode short_id_ordered()");
+    assert!(
+        before.duration_since(created).unwrap_or_default() < std::time::Duration::from_millis(1),
+        "decoded timestamp should be close to the generation time"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_ordered_layout_custom_epoch() {
+    use short_id::{OrderedLayout, Precision};
+    use std::time::Duration;
+
+    const LAYOUT: OrderedLayout = OrderedLayout::new()
+        .with_precision(Precision::Milliseconds)
+        .with_timestamp_bytes(6)
+        .with_epoch(Duration::from_secs(1_577_836_800));
+
+    let id = LAYOUT.generate(10).unwrap();
+    assert_eq!(
+        id.len(),
+        14,
+        "tag byte + 6-byte timestamp + 3 random bytes should be 14 chars"
+    );
+    let created = LAYOUT
+        .timestamp(&id)
+        .expect("layout should decode its own IDs");
+    assert!(
+        created > LAYOUT.epoch(),
+        "decoded timestamp should be after the custom epoch"
+    );
+}
+
+// Tests for SnowflakeGenerator
+#[cfg(feature = "std")]
+#[test]
+fn test_snowflake_generators_on_different_nodes_never_collide() {
+    use short_id::{SnowflakeGenerator, SnowflakeLayout};
+
+    let node_a = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 1);
+    let node_b = SnowflakeGenerator::new(SnowflakeLayout::DEFAULT, 2);
+
+    let mut ids = std::collections::HashSet::new();
+    for _ in 0..1000 {
+        assert!(
+            ids.insert(node_a.generate().unwrap()),
+            "duplicate ID from node 1"
+        );
+        assert!(
+            ids.insert(node_b.generate().unwrap()),
+            "duplicate ID from node 2"
+        );
+    }
+
+    for id in &ids {
+        let parts = SnowflakeLayout::DEFAULT.decode(id.as_str()).unwrap();
+        assert!(
+            parts.node == 1 || parts.node == 2,
+            "unexpected node {}",
+            parts.node
+        );
+    }
+}
+
+// Tests for OrderedGenerator
+#[cfg(feature = "std")]
+#[test]
+fn test_ordered_generator_ids_are_ordered_over_time() {
+    use short_id::{BinaryId, OrderedGenerator, OrderedLayout, RegressionPolicy};
+
+    let generator =
+        OrderedGenerator::new(OrderedLayout::DEFAULT, 10).with_policy(RegressionPolicy::Wait);
+    let mut ids = Vec::new();
+    for _ in 0..5 {
+        ids.push(BinaryId::from(
+            generator
+                .generate()
+                .expect("Wait policy never returns an error"),
+        ));
+        std::thread::sleep(std::time::Duration::from_micros(10));
+    }
+
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(ids, sorted, "IDs generated over time should sort in order");
+    assert_eq!(
+        generator.regressions(),
+        0,
+        "clock should not have regressed"
+    );
+}
+
+// Tests for HlcGenerator
+#[cfg(feature = "std")]
+#[test]
+fn test_hlc_ids_respect_causality_across_nodes() {
+    use short_id::{BinaryId, HlcGenerator, HlcTimestamp};
+
+    let node_a = HlcGenerator::new(12);
+    let node_b = HlcGenerator::new(12);
+
+    // A message bounces between the nodes several times
+    let mut last = node_a.generate();
+    for round in 0..10 {
+        let receiver = if round % 2 == 0 { &node_b } else { &node_a };
+        receiver
+            .observe(&last)
+            .expect("HLC IDs should be observable");
+        let next = receiver.generate();
+        assert!(
+            BinaryId::from(next.clone()) > BinaryId::from(last.clone()),
+            "reply {} should sort after {}",
+            next,
+            last
+        );
+        assert!(
+            HlcTimestamp::decode(next.as_str()).unwrap()
+                > HlcTimestamp::decode(last.as_str()).unwrap(),
+            "clock readings should follow the same order as the IDs"
+        );
+        last = next;
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_descending_layout_lists_newest_first() {
+    use short_id::{BinaryId, OrderedLayout, Precision, SortOrder};
+    use std::time::{Duration, SystemTime};
+
+    const NEWEST_FIRST: OrderedLayout = OrderedLayout::new()
+        .with_precision(Precision::Milliseconds)
+        .with_order(SortOrder::Descending);
+
+    let start = SystemTime::now();
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        ids.push(BinaryId::from(ShortId::from(
+            NEWEST_FIRST.generate(10).unwrap(),
+        )));
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    let end = SystemTime::now();
+
+    // An ascending scan over the range returns the newest ID first
+    let range = NEWEST_FIRST.range(start, end, 10);
+    let mut scanned: Vec<&BinaryId> = ids.iter().filter(|id| range.contains(*id)).collect();
+    scanned.sort();
+    assert_eq!(
+        scanned,
+        vec![&ids[2], &ids[1], &ids[0]],
+        "newest ID should sort first"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_sharded_layout_scans_find_every_id() {
+    use short_id::{BinaryId, OrderedLayout, Precision};
+    use std::time::{Duration, SystemTime};
+
+    const SHARDED: OrderedLayout = OrderedLayout::new()
+        .with_precision(Precision::Milliseconds)
+        .with_timestamp_bytes(6)
+        .with_buckets(16);
+
+    let start = SystemTime::now() - Duration::from_millis(1);
+    let ids: Vec<BinaryId> = (0..200)
+        .map(|_| BinaryId::from(ShortId::from(SHARDED.generate(10).unwrap())))
+        .collect();
+    let end = SystemTime::now();
+
+    let buckets: std::collections::HashSet<u16> = ids
+        .iter()
+        .map(|id| SHARDED.bucket(id.0.as_str()).unwrap())
+        .collect();
+    assert!(
+        buckets.len() > 1,
+        "IDs should be spread over several buckets"
+    );
+
+    let scans = SHARDED.ranges(start, end, 10);
+    assert_eq!(scans.len(), 16, "there should be one scan range per bucket");
+    for id in &ids {
+        assert_eq!(
+            scans.iter().filter(|range| range.contains(id)).count(),
+            1,
+            "each ID should fall in exactly one bucket range"
+        );
+    }
+}
+
+// Tests for content-addressed IDs
+#[cfg(feature = "content")]
+#[test]
+fn test_content_ids_are_stable_and_decodable() {
+    use short_id::{short_id_from_content, short_id_from_namespaced_content};
+
+    let id = short_id_from_content(b"assets/logo.png");
+    assert_eq!(
+        id,
+        short_id_from_content(b"assets/logo.png"),
+        "same content, same ID"
+    );
+    assert_eq!(id.len(), 14, "content IDs should match short_id() length");
+
+    let typed = ShortId::from_content(b"assets/logo.png");
+    assert_eq!(
+        typed.as_str(),
+        id,
+        "ShortId::from_content should match the function"
+    );
+    assert_eq!(
+        typed.to_bytes().unwrap().len(),
+        10,
+        "content IDs should decode to 10 bytes"
+    );
+
+    assert_ne!(
+        short_id_from_namespaced_content("cache", b"assets/logo.png"),
+        id,
+        "namespaced IDs should differ from plain content IDs"
+    );
+}
+
+// Tests for integer key obfuscation
+#[cfg(feature = "obfuscate")]
+#[test]
+fn test_obfuscated_keys_roundtrip() {
+    use short_id::IdObfuscator;
+
+    let obfuscator = IdObfuscator::new(b"integration test key");
+    for key in 1..=1_000u64 {
+        let id = obfuscator.encode(key);
+        assert_eq!(id.len(), 11, "obfuscated IDs should have a fixed length");
+        assert_eq!(
+            obfuscator.decode(&id),
+            Ok(key),
+            "decode should invert encode"
+        );
+    }
+}
+
+// Tests for encrypted ordered IDs
+#[cfg(feature = "encrypted")]
+#[test]
+fn test_encrypted_ordered_ids_decrypt_in_order() {
+    use short_id::{BinaryId, OrderedCipher, OrderedLayout};
+
+    let cipher = OrderedCipher::new(&[9u8; 32]);
+    let mut plain = Vec::new();
+    for _ in 0..5 {
+        let id = cipher.generate().unwrap();
+        assert_eq!(
+            id.as_str().len(),
+            22,
+            "encrypted IDs should be 22 characters"
+        );
+        plain.push(cipher.decrypt(id.as_str()).unwrap());
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+
+    let mut sorted = plain.clone();
+    sorted.sort_by_key(|id| BinaryId::from(ShortId::from(id.clone())));
+    assert_eq!(plain, sorted, "decrypted IDs should sort by creation time");
+    for id in &plain {
+        assert!(OrderedLayout::DEFAULT.timestamp(id).unwrap() <= std::time::SystemTime::now());
+    }
+}
+
+// Tests for signed IDs
+#[cfg(feature = "signed")]
+#[test]
+fn test_signed_ids_survive_key_rotation() {
+    use short_id::{SignedIdGenerator, VerifyError};
+
+    let before = SignedIdGenerator::new(b"2024 key");
+    let invite = ShortId::random();
+    let old_link = before.sign(&invite).unwrap();
+
+    let after = SignedIdGenerator::new(b"2024 key").with_signing_key(1, b"2025 key");
+    assert_eq!(
+        after.verify(&old_link),
+        Ok(invite),
+        "old links should still verify"
+    );
+
+    let new_link = after.generate();
+    assert_eq!(
+        before.verify(new_link.as_str()),
+        Err(VerifyError::UnknownKey(1)),
+        "verifiers without the new key should reject new links"
+    );
+}
+
+// Tests for self-expiring tokens
+#[cfg(feature = "std")]
+#[test]
+fn test_expiring_tokens_with_injected_clock() {
+    use short_id::{ExpiringTokenGenerator, TokenError};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    let now = Arc::new(Mutex::new(SystemTime::now()));
+    let clock = {
+        let now = Arc::clone(&now);
+        move || *now.lock().unwrap()
+    };
+    let tokens = ExpiringTokenGenerator::new(Duration::from_secs(300)).with_clock(clock);
+    let token = tokens.generate();
+    assert!(
+        tokens.validate(token.as_str()).is_ok(),
+        "fresh tokens should validate"
+    );
+
+    *now.lock().unwrap() += Duration::from_secs(301);
+    assert_eq!(
+        tokens.validate(token.as_str()),
+        Err(TokenError::Expired(token.expires_at())),
+        "tokens should expire after their time-to-live"
+    );
+}
+
+// Tests for check characters
+#[test]
+fn test_checked_ids_reject_typos() {
+    use short_id::{short_id_checked, DecodeError};
+
+    let checked = short_id_checked();
+    let id = ShortId::parse_checked(&checked).unwrap();
+    assert_eq!(
+        id.to_bytes().unwrap().len(),
+        10,
+        "checked IDs wrap a 10-byte ID"
+    );
+
+    let mut typo = checked.clone().into_bytes();
+    typo[0] = if typo[0] == b'x' { b'y' } else { b'x' };
+    assert_eq!(
+        ShortId::parse_checked(&String::from_utf8(typo).unwrap()),
+        Err(DecodeError::InvalidCheckCharacter),
+        "a single mistyped character should be detected"
+    );
+}
+
+#[test]
+fn test_parse_validates_encoding() {
+    let id: ShortId = short_id().parse().unwrap();
+    assert_eq!(id.to_bytes().unwrap().len(), 10);
+    assert!(
+        "bad id!".parse::<ShortId>().is_err(),
+        "parse should reject invalid IDs"
+    );
+}
+
+#[test]
+fn test_parse_rejects_empty_id() {
+    use short_id::DecodeError;
+
+    assert_eq!(ShortId::parse(""), Err(DecodeError::InvalidLength(0)));
+    assert_eq!("".parse::<ShortId>(), Err(DecodeError::InvalidLength(0)));
+}
+
+// Tests for Bech32m IDs
+#[cfg(feature = "std")]
+#[test]
+fn test_bech32_ids_roundtrip_ordered_layout() {
+    use short_id::{short_id_ordered_bech32, OrderedLayout};
+
+    let encoded = short_id_ordered_bech32("evt");
+    assert!(
+        encoded.starts_with("evt1"),
+        "Bech32m IDs should start with the prefix"
+    );
+    assert!(
+        encoded
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()),
+        "Bech32m IDs should be lowercase alphanumeric"
+    );
+
+    let id = ShortId::from_bech32(&encoded, "evt").unwrap();
+    assert!(
+        OrderedLayout::DEFAULT.timestamp(id.as_str()).is_ok(),
+        "parsed IDs should keep the ordered layout"
+    );
+    assert_eq!(id.to_bech32("evt").unwrap(), encoded);
+}
+
+// Tests for secret tokens
+#[test]
+fn test_secret_tokens_match_scanner_pattern() {
+    use short_id::{SecretToken, SecretTokenError};
+
+    let token = SecretToken::generate("acme");
+    let (prefix, body) = token.as_str().split_once('_').unwrap();
+    assert_eq!(prefix, "acme");
+    assert_eq!(
+        body.len(),
+        36,
+        "tokens should have 36 characters after the prefix"
+    );
+    assert!(
+        body.chars().all(|c| c.is_ascii_alphanumeric()),
+        "tokens should be base62"
+    );
+
+    assert_eq!(SecretToken::validate_format(token.as_str(), "acme"), Ok(()));
+    let noise = format!("acme_{}", "x".repeat(36));
+    assert_eq!(
+        SecretToken::validate_format(&noise, "acme"),
+        Err(SecretTokenError::InvalidChecksum),
+        "random matches of the pattern should fail the checksum"
+    );
+}
+
+// Tests for secret IDs
+#[cfg(feature = "secret")]
+#[test]
+fn test_secret_ids_do_not_leak_through_debug() {
+    use short_id::SecretId;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Session {
+        user: &'static str,
+        token: SecretId,
+    }
+
+    let session = Session {
+        user: "alice",
+        token: SecretId::random(),
+    };
+    let logged = format!("{:?}", session);
+    assert!(
+        !logged.contains(session.token.expose_secret()),
+        "Debug output should not contain the secret"
+    );
+    assert!(logged.contains("SecretId(***)"));
+}
+
+// Tests for hashed token storage
+#[cfg(feature = "secret")]
+#[test]
+fn test_stored_tokens_are_found_by_prefix_and_verified_by_hash() {
+    use short_id::{short_id_with_bytes, StoredToken, TokenDigest};
+    use std::collections::HashMap;
+
+    // A "table" indexed by lookup prefix, holding digests only
+    let mut table: HashMap<String, String> = HashMap::new();
+    let token = short_id_with_bytes(24);
+    let stored = StoredToken::new(&token, 8);
+    table.insert(stored.lookup().to_string(), stored.digest().to_string());
+    assert!(
+        !table.values().any(|digest| digest.contains(&token)),
+        "the token itself should not be stored"
+    );
+
+    let key = StoredToken::lookup_key(&token, 8).unwrap();
+    let digest = TokenDigest::parse(&table[key]).unwrap();
+    let row = StoredToken::from_parts(key.to_string(), digest);
+    assert!(row.verify(&token), "the presented token should verify");
+    assert!(!row.verify(&short_id_with_bytes(24)));
+}
+
+// Tests for sqlx support
+#[cfg(feature = "sqlx-sqlite")]
+#[tokio::test]
+async fn test_sqlx_stores_ids_as_text_and_bytes() {
+    use short_id::BinaryId;
+    use sqlx::{Connection, Row, SqliteConnection};
+
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+    sqlx::query("CREATE TABLE items (id TEXT PRIMARY KEY, raw BLOB NOT NULL)")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let id = ShortId::random();
+    sqlx::query("INSERT INTO items (id, raw) VALUES (?, ?)")
+        .bind(&id)
+        .bind(BinaryId(id.clone()))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let row = sqlx::query("SELECT id, raw FROM items WHERE id = ?")
+        .bind(&id)
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(row.get::<ShortId, _>("id"), id);
+    assert_eq!(row.get::<BinaryId, _>("raw").into_inner(), id);
+}
+
+// Tests for Diesel support
+#[cfg(feature = "diesel-sqlite")]
+#[test]
+fn test_diesel_typed_id_newtype_in_schema() {
+    use diesel::{
+        connection::SimpleConnection,
+        deserialize::{self, FromSql, FromSqlRow},
+        expression::AsExpression,
+        prelude::*,
+        serialize::{self, Output, ToSql},
+        sql_types::Text,
+        sqlite::{Sqlite, SqliteConnection},
+    };
+
+    diesel::table! {
+        users (id) {
+            id -> Text,
+            name -> Text,
+        }
+    }
+
+    // A typed ID delegating to ShortId's impls
+    #[derive(Debug, Clone, PartialEq, AsExpression, FromSqlRow)]
+    #[diesel(sql_type = Text)]
+    struct UserId(ShortId);
+
+    impl ToSql<Text, Sqlite> for UserId {
+        fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+            <ShortId as ToSql<Text, Sqlite>>::to_sql(&self.0, out)
+        }
+    }
+
+    impl FromSql<Text, Sqlite> for UserId {
+        fn from_sql(
+            bytes: <Sqlite as diesel::backend::Backend>::RawValue<'_>,
+        ) -> deserialize::Result<Self> {
+            <ShortId as FromSql<Text, Sqlite>>::from_sql(bytes).map(UserId)
+        }
+    }
+
+    #[derive(Queryable, Insertable, Debug, PartialEq)]
+    #[diesel(table_name = users)]
+    struct User {
+        id: UserId,
+        name: String,
+    }
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.batch_execute("CREATE TABLE users (id TEXT PRIMARY KEY, name TEXT NOT NULL)")
+        .unwrap();
+
+    let user = User {
+        id: UserId(ShortId::random()),
+        name: "alice".to_string(),
+    };
+    diesel::insert_into(users::table)
+        .values(&user)
+        .execute(&mut conn)
+        .unwrap();
+
+    let loaded: User = users::table
+        .filter(users::id.eq(&user.id))
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(loaded, user);
+}
+
+// Tests for rusqlite support
+#[cfg(feature = "rusqlite")]
+#[test]
+fn test_rusqlite_lookup_by_id() {
+    use rusqlite::{Connection, OptionalExtension};
+    use short_id::BinaryId;
+
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("CREATE TABLE jobs (id BLOB PRIMARY KEY, name TEXT NOT NULL)")
+        .unwrap();
+
+    let id = ShortId::ordered();
+    conn.execute(
+        "INSERT INTO jobs VALUES (?1, 'backup')",
+        [BinaryId(id.clone())],
+    )
+    .unwrap();
+
+    let name: Option<String> = conn
+        .query_row(
+            "SELECT name FROM jobs WHERE id = ?1",
+            [BinaryId(id)],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap();
+    assert_eq!(name.as_deref(), Some("backup"));
+}
+
+// Tests for postgres-types support
+#[cfg(feature = "postgres")]
+#[test]
+fn test_postgres_wire_format() {
+    use bytes::BytesMut;
+    use postgres_types::{FromSql, ToSql, Type};
+
+    let id = ShortId::ordered();
+    for ty in [Type::TEXT, Type::VARCHAR, Type::BYTEA] {
+        let mut buf = BytesMut::new();
+        id.to_sql_checked(&ty, &mut buf).unwrap();
+        assert_eq!(ShortId::from_sql(&ty, &buf).unwrap(), id);
+    }
+
+    // Optional columns go through the same impls
+    let mut buf = BytesMut::new();
+    Some(id.clone())
+        .to_sql_checked(&Type::TEXT, &mut buf)
+        .unwrap();
+    assert_eq!(
+        Option::<ShortId>::from_sql(&Type::TEXT, &buf).unwrap(),
+        Some(id)
+    );
+}
+
+// Tests for BSON conversions
+#[cfg(feature = "bson")]
+#[test]
+fn test_bson_id_fields() {
+    use bson::{doc, Bson};
+    use short_id::BinaryId;
+
+    let id = ShortId::ordered();
+    let as_string = doc! { "_id": id.clone(), "name": "report" };
+    let as_binary = doc! { "_id": Bson::try_from(BinaryId(id.clone())).unwrap() };
+
+    for document in [&as_string, &as_binary] {
+        let stored = document.get("_id").unwrap();
+        assert_eq!(ShortId::try_from(stored).unwrap(), id);
+    }
+}
+
+// Tests for the Protocol Buffers message
+#[cfg(feature = "prost")]
+#[test]
+fn test_proto_message_as_a_field() {
+    use prost::Message;
+    use short_id::proto;
+
+    // A message from another package embedding short_id.v1.ShortId, as prost generates it
+    #[derive(Clone, PartialEq, Message)]
+    struct GetOrderRequest {
+        #[prost(message, optional, tag = "1")]
+        order_id: Option<proto::v1::ShortId>,
+    }
+
+    let id = ShortId::random();
+    let request = GetOrderRequest {
+        order_id: Some(proto::v1::ShortId::try_from(&id).unwrap()),
+    };
+    let wire = request.encode_to_vec();
+
+    let received = GetOrderRequest::decode(wire.as_slice()).unwrap();
+    let order_id = ShortId::try_from(received.order_id.unwrap()).unwrap();
+    assert_eq!(order_id, id);
+}
+
+// Tests for API schemas
+#[cfg(feature = "utoipa")]
+#[test]
+fn test_openapi_schema_for_typed_id() {
+    use short_id::IdFormat;
+    use std::borrow::Cow;
+    use utoipa::openapi::{schema::Schema, RefOr};
+    use utoipa::{PartialSchema, ToSchema};
+
+    const ORDER_ID: IdFormat = IdFormat::new().with_prefix("ord_").with_bytes(10);
+
+    struct OrderId(#[allow(dead_code)] ShortId);
+
+    impl PartialSchema for OrderId {
+        fn schema() -> RefOr<Schema> {
+            ORDER_ID.openapi_schema().into()
+        }
+    }
+
+    impl ToSchema for OrderId {
+        fn name() -> Cow<'static, str> {
+            Cow::Borrowed("OrderId")
+        }
+    }
+
+    let object = match OrderId::schema() {
+        RefOr::T(Schema::Object(object)) => object,
+        _ => panic!("expected an object schema"),
+    };
+    assert_eq!(object.pattern.as_deref(), Some("^ord_[-0-9A-Z_a-z]{14}$"));
+    assert_eq!((object.min_length, object.max_length), (Some(18), Some(18)));
+
+    let example = ORDER_ID.example();
+    let id = example.strip_prefix("ord_").unwrap();
+    assert!(
+        ShortId::parse(id).is_ok(),
+        "the example should be a valid ID"
+    );
+}
+
+// Tests for the axum extractor
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn test_axum_rejection_renders_400() {
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+        response::IntoResponse,
+        routing::delete,
+        Router,
+    };
+    use short_id::IdRejection;
+    use tower::ServiceExt;
+
+    async fn delete_order(id: Result<ShortId, IdRejection>) -> axum::response::Response {
+        match id {
+            Ok(id) => format!("deleted {}", id).into_response(),
+            Err(rejection) => {
+                assert!(matches!(rejection, IdRejection::InvalidId(_)));
+                rejection.into_response()
+            }
+        }
+    }
+
+    let app = Router::new().route("/orders/{id}", delete(delete_order));
+    let request = Request::delete("/orders/%2A%2A")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(
+        &body[..],
+        b"invalid path parameter: invalid short ID encoding"
+    );
+
+    let id = short_id_ordered();
+    let request = Request::delete(format!("/orders/{}", id))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+// Tests for the request ID middleware
+#[cfg(all(feature = "tower", feature = "axum"))]
+#[tokio::test]
+async fn test_request_id_layer_in_axum_app() {
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+        routing::get,
+        Extension, Router,
+    };
+    use short_id::{BinaryId, RequestIdLayer};
+    use tower::ServiceExt;
+
+    let app = Router::new()
+        .route(
+            "/",
+            get(|Extension(id): Extension<ShortId>| async move { id.to_string() }),
+        )
+        .layer(RequestIdLayer::new());
+
+    // A valid ID from the client is kept and echoed back
+    let sent = ShortId::ordered();
+    let request = Request::get("/")
+        .header("x-request-id", sent.as_str())
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.headers()["x-request-id"], sent.as_str());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&body[..], sent.as_str().as_bytes());
+
+    // A malformed one is replaced with a new ordered ID
+    let request = Request::get("/")
+        .header("x-request-id", "../../etc/passwd")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let minted = ShortId::parse(response.headers()["x-request-id"].to_str().unwrap()).unwrap();
+    assert_ne!(minted, sent);
+    assert!(
+        BinaryId::from(minted) > BinaryId::from(sent),
+        "minted IDs should sort after earlier ones"
+    );
+}